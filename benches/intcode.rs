@@ -0,0 +1,120 @@
+// Interpreter against the pre-decoded executor, run with `cargo bench --bench intcode`
+use adv_rs_2019::intcode::asm::assemble;
+use adv_rs_2019::intcode::compiled::CompiledIntCode;
+use adv_rs_2019::intcode::IntCode;
+use std::time::{Duration, Instant};
+
+const REPEAT: usize = 5;
+
+// Best of REPEAT runs
+fn time<F: FnMut() -> i64>(mut f: F) -> (Duration, i64) {
+    let mut best = Duration::MAX;
+    let mut result = 0;
+    for _ in 0..REPEAT {
+        let start = Instant::now();
+        result = f();
+        best = best.min(start.elapsed());
+    }
+    (best, result)
+}
+
+fn report(name: &str, interpreted: (Duration, i64), compiled: (Duration, i64)) {
+    assert_eq!(interpreted.1, compiled.1, "{} gave different results", name);
+    println!(
+        "{:<12} {:>12.3?} {:>12.3?} {:>8.2}x",
+        name,
+        interpreted.0,
+        compiled.0,
+        interpreted.0.as_secs_f64() / compiled.0.as_secs_f64()
+    );
+}
+
+fn main() {
+    // Sums i * i for i below the input, one long run
+    let squares = assemble(
+        "
+                IN [n]
+        loop:   MUL [i], [i], [sq]
+                ADD [sum], [sq], [sum]
+                ADD [i], #1, [i]
+                LT [i], [n], [more]
+                JNZ [more], #loop
+                OUT [sum]
+                HLT
+        n:      DATA 0
+        i:      DATA 0
+        sq:     DATA 0
+        sum:    DATA 0
+        more:   DATA 0
+        ",
+    )
+    .unwrap();
+
+    // Reads x and y and decides whether the point is inside a cone, like the day 19 drone
+    let drone = assemble(
+        "
+                IN [x]
+                IN [y]
+                ARB #100
+                MUL [x], #3, rb[0]
+                MUL [y], #2, rb[1]
+                ADD #0, #0, [i]
+        spin:   ADD [i], #1, [i]
+                LT [i], #20, [t]
+                JNZ [t], #spin
+                LT rb[0], rb[1], [t]
+                OUT [t]
+                HLT
+        x:      DATA 0
+        y:      DATA 0
+        i:      DATA 0
+        t:      DATA 0
+        ",
+    )
+    .unwrap();
+
+    println!(
+        "{:<12} {:>12} {:>12} {:>9}",
+        "workload", "interpreter", "compiled", "speedup"
+    );
+
+    let interpreted = time(|| {
+        let mut intcode = IntCode::new(&squares);
+        intcode.input.push(200_000);
+        intcode.run_until_blocked().unwrap().0[0]
+    });
+    let compiled = time(|| {
+        let mut intcode = CompiledIntCode::new(&squares);
+        intcode.input().push(200_000);
+        intcode.run_until_blocked().unwrap().0[0]
+    });
+    report("long run", interpreted, compiled);
+
+    let interpreted = time(|| {
+        let pristine = IntCode::new(&drone);
+        let mut inside = 0;
+        for y in 0..100 {
+            for x in 0..100 {
+                let mut intcode = pristine.clone();
+                intcode.input.push(x);
+                intcode.input.push(y);
+                inside += intcode.run_until_blocked().unwrap().0[0];
+            }
+        }
+        inside
+    });
+    let compiled = time(|| {
+        let pristine = CompiledIntCode::new(&drone);
+        let mut inside = 0;
+        for y in 0..100 {
+            for x in 0..100 {
+                let mut intcode = pristine.clone();
+                intcode.input().push(x);
+                intcode.input().push(y);
+                inside += intcode.run_until_blocked().unwrap().0[0];
+            }
+        }
+        inside
+    });
+    report("fresh runs", interpreted, compiled);
+}
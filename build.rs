@@ -9,7 +9,7 @@ use std::{
 };
 
 fn days(input_dir: &str) -> io::Result<Vec<u32>> {
-    Ok(read_dir(input_dir)?
+    let mut days: Vec<u32> = read_dir(input_dir)?
         .flatten()
         .filter(|e| e.path().is_file())
         .flat_map(|e| e.file_name().into_string())
@@ -18,29 +18,42 @@ fn days(input_dir: &str) -> io::Result<Vec<u32>> {
                 .ok()
                 .and_then(|v| v.parse::<u32>().ok())
         })
-        .collect())
+        .collect();
+    // read_dir order isn't stable, keep the generated file the same between builds
+    days.sort_unstable();
+    Ok(days)
 }
 
 fn gen_solutions_mod<P: AsRef<Path>>(p: P, days: &[u32]) -> io::Result<()> {
     let mut f = File::create(p)?;
     writeln!(f, "// DO NOT EDIT THIS FILE")?;
-    writeln!(f, "use crate::solver::Solver;")?;
+    writeln!(
+        f,
+        "use crate::solver::{{InputError, Part, Report, Solver}};"
+    )?;
     writeln!(f)?;
     for day in days {
         writeln!(f, "mod day{0:02};", day)?;
     }
     writeln!(f)?;
+    let list: Vec<String> = days.iter().map(|d| d.to_string()).collect();
+    writeln!(f, "pub const DAYS: &[i32] = &[{}];", list.join(", "))?;
+    writeln!(f)?;
     writeln!(
         f,
-        "pub fn exec_day(day: i32) {{
+        "pub fn exec_day(day: i32, input: &str, part: Part) -> Result<Report, InputError> {{
     match day {{"
     )?;
     for day in days {
-        writeln!(f, "        {0} => day{0:02}::Problem {{}}.solve(day),", day)?;
+        writeln!(
+            f,
+            "        {0} => day{0:02}::Problem {{}}.run(input, part),",
+            day
+        )?;
     }
     writeln!(
         f,
-        "        d => println!(\"Day {{}} hasn't been solved yet :(\", d),
+        "        d => panic!(\"Day {{}} hasn't been solved yet :(\", d),
     }}
 }}"
     )?;
@@ -58,7 +71,8 @@ fn gen_solutions(dir: &str, days: &[u32]) -> io::Result<()> {
         let mut f = File::create(file)?;
         writeln!(
             f,
-            "use crate::solver::Solver;
+            "use crate::parse::ParseError;
+use crate::solver::Solver;
 use std::io::Read;
 
 pub struct Problem;
@@ -68,7 +82,9 @@ impl Solver for Problem {{
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {{}}
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {{
+        Ok(())
+    }}
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {{
         0
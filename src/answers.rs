@@ -0,0 +1,188 @@
+use crate::parse::{column, parse_lines, read_text, ParseError};
+use crate::solver::InputError;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+pub const ANSWERS_DIR: &str = "answers";
+
+pub fn answers_file(dir: &str, day: i32) -> String {
+    format!("{}/day{:02}.toml", dir, day)
+}
+
+// The known answers for one input, `None` for a part not yet recorded
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Answers {
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    // Nothing recorded to compare against
+    New,
+}
+
+pub fn check(expected: Option<&str>, actual: &str) -> Verdict {
+    match expected {
+        None => Verdict::New,
+        Some(expected) if expected == actual => Verdict::Pass,
+        Some(_) => Verdict::Fail,
+    }
+}
+
+impl Answers {
+    // Only the bit of TOML the sidecar files need: comments and `part1`/`part2` keys whose
+    // values are strings or bare numbers
+    pub fn parse(text: &str) -> Result<Answers, ParseError> {
+        let mut answers = Answers::default();
+        let mut seen = Vec::new();
+        let entries = parse_lines(text, |line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') {
+                return Ok(None);
+            }
+            let indent = line.len() - trimmed.len();
+            let equals = line
+                .find('=')
+                .ok_or_else(|| ParseError::at(column(line, indent), "expected 'key = value'"))?;
+            let key = line[..equals].trim();
+            if seen.iter().any(|k| k == key) {
+                return Err(ParseError::at(
+                    column(line, indent),
+                    format!("duplicate key '{}'", key),
+                ));
+            }
+            seen.push(key.to_string());
+            match key {
+                "part1" | "part2" => Ok(Some((key == "part1", value(line, equals + 1)?))),
+                _ => Err(ParseError::at(
+                    column(line, indent),
+                    format!("unknown key '{}', expected part1 or part2", key),
+                )),
+            }
+        })?;
+        for (first, value) in entries.into_iter().flatten() {
+            if first {
+                answers.first = Some(value);
+            } else {
+                answers.second = Some(value);
+            }
+        }
+        Ok(answers)
+    }
+
+    // A missing file just means nothing has been recorded yet
+    pub fn load(path: &str) -> Result<Answers, InputError> {
+        let f = match File::open(path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Answers::default()),
+            Err(e) => return Err(InputError::Io(path.to_string(), e)),
+        };
+        read_text(f)
+            .and_then(|text| Answers::parse(&text))
+            .map_err(|e| InputError::Parse(path.to_string(), e))
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut toml = String::new();
+        for (key, value) in [("part1", &self.first), ("part2", &self.second)] {
+            if let Some(value) = value {
+                toml.push_str(&format!("{} = {}\n", key, quote(value)));
+            }
+        }
+        toml
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+}
+
+// The value starting at byte `start` of `line`
+fn value(line: &str, start: usize) -> Result<String, ParseError> {
+    let rest = &line[start..];
+    let offset = start + rest.len() - rest.trim_start().len();
+    let rest = rest.trim();
+    let error = |at: usize, message: &str| ParseError::at(column(line, at), message);
+
+    if !rest.starts_with('"') {
+        // Bare values are numbers, possibly followed by a comment
+        let bare = rest.split('#').next().unwrap().trim_end();
+        if bare.is_empty() || bare.parse::<i128>().is_err() {
+            return Err(error(offset, "expected a quoted string or a number"));
+        }
+        return Ok(bare.to_string());
+    }
+
+    let mut value = String::new();
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let after = rest[i + 1..].trim_start();
+                if !after.is_empty() && !after.starts_with('#') {
+                    return Err(error(offset + i + 1, "unexpected text after the value"));
+                }
+                return Ok(value);
+            }
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, 'n')) => value.push('\n'),
+                _ => return Err(error(offset + i, "unsupported escape sequence")),
+            },
+            c => value.push(c),
+        }
+    }
+    Err(error(offset, "unterminated string"))
+}
+
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+#[test]
+fn test_answers() {
+    let answers = Answers::parse("# Day 4\npart1 = 1135\n\npart2 = \"FPU\\\"AR\" # image\n");
+    assert_eq!(
+        answers,
+        Ok(Answers {
+            first: Some(String::from("1135")),
+            second: Some(String::from("FPU\"AR")),
+        })
+    );
+    let answers = answers.unwrap();
+    assert_eq!(Answers::parse(&answers.to_toml()), Ok(answers));
+
+    assert_eq!(
+        Answers::parse("part1 = 1\npart3 = 2"),
+        Err(ParseError::new(
+            2,
+            1,
+            "unknown key 'part3', expected part1 or part2"
+        ))
+    );
+    assert_eq!(
+        Answers::parse("part1 = \"abc"),
+        Err(ParseError::new(1, 9, "unterminated string"))
+    );
+    assert_eq!(Answers::parse("part2 = x").unwrap_err().column, 9);
+    assert_eq!(
+        Answers::parse("part1 = 1\n# again\n  part1 = 2"),
+        Err(ParseError::new(3, 3, "duplicate key 'part1'"))
+    );
+
+    assert_eq!(check(Some("12"), "12"), Verdict::Pass);
+    assert_eq!(check(Some("12"), "13"), Verdict::Fail);
+    assert_eq!(check(None, "12"), Verdict::New);
+}
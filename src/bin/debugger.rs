@@ -0,0 +1,50 @@
+use adv_rs_2019::intcode::debugger::Debugger;
+use adv_rs_2019::intcode::{read_input, IntCode};
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, Write};
+use std::process;
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: debugger <program file>");
+            process::exit(2);
+        }
+    };
+    let file = File::open(&path).unwrap_or_else(|e| {
+        eprintln!("Unable to open {}: {}", path, e);
+        process::exit(1);
+    });
+    let program = read_input(file).unwrap_or_else(|e| {
+        eprintln!("{}:{}:{}: {}", path, e.line, e.column, e.message);
+        process::exit(1);
+    });
+    let mut debugger = Debugger::new(IntCode::new(&program));
+    println!("Type 'h' for help");
+    println!("{}", debugger.current());
+
+    // An empty line repeats the previous command
+    let mut last_command = String::new();
+    loop {
+        print!("(icdb) ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let command = match line.trim() {
+            "" => last_command.clone(),
+            command => command.to_string(),
+        };
+        if command == "q" || command == "quit" {
+            break;
+        }
+        if !command.is_empty() {
+            println!("{}", debugger.execute(&command));
+        }
+        last_command = command;
+    }
+}
@@ -0,0 +1,31 @@
+use adv_rs_2019::intcode::cfg::build_cfg;
+use adv_rs_2019::intcode::disasm::disassemble;
+use adv_rs_2019::intcode::read_input;
+use std::env;
+use std::fs::File;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (path, dot) = match args.as_slice() {
+        [path] => (path, false),
+        [path, flag] if flag == "--dot" => (path, true),
+        _ => {
+            eprintln!("Usage: disasm <program file> [--dot]");
+            process::exit(2);
+        }
+    };
+    let file = File::open(path).unwrap_or_else(|e| {
+        eprintln!("Unable to open {}: {}", path, e);
+        process::exit(1);
+    });
+    let program = read_input(file).unwrap_or_else(|e| {
+        eprintln!("{}:{}:{}: {}", path, e.line, e.column, e.message);
+        process::exit(1);
+    });
+    if dot {
+        print!("{}", build_cfg(&program).to_dot());
+    } else {
+        print!("{}", disassemble(&program));
+    }
+}
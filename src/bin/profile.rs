@@ -0,0 +1,139 @@
+use adv_rs_2019::intcode::{
+    read_input, IntCode, IntcodeError, Profiler, RunState, TraceEvent, Tracer, WriterTracer,
+};
+use std::env;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process;
+use std::sync::{Arc, Mutex};
+
+const USAGE: &str = "Usage: profile <program file> [--input v1,v2,...] [--trace <file>] [--top n]";
+
+// Profiles the run, optionally writing the full trace alongside
+struct ProfileTracer<W: Write + Send> {
+    profiler: Profiler,
+    trace: Option<WriterTracer<W>>,
+}
+
+impl<W: Write + Send> Tracer for ProfileTracer<W> {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.profiler.trace(event);
+        if let Some(trace) = &mut self.trace {
+            trace.trace(event);
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let path = args.first().unwrap_or_else(|| fail(USAGE));
+    let mut inputs = Vec::new();
+    let mut trace = None;
+    let mut top = 20;
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1).unwrap_or_else(|| fail(USAGE));
+        match args[i].as_str() {
+            "--input" => {
+                inputs = value
+                    .split(',')
+                    .map(|v| v.trim().parse::<i64>())
+                    .collect::<Result<Vec<i64>, _>>()
+                    .unwrap_or_else(|_| fail("Invalid input values"))
+            }
+            "--trace" => {
+                let file = File::create(value)
+                    .unwrap_or_else(|e| fail(&format!("Unable to create {}: {}", value, e)));
+                trace = Some(BufWriter::new(file));
+            }
+            "--top" => top = value.parse().unwrap_or_else(|_| fail(USAGE)),
+            _ => fail(USAGE),
+        }
+        i += 2;
+    }
+
+    let file =
+        File::open(path).unwrap_or_else(|e| fail(&format!("Unable to open {}: {}", path, e)));
+    let program = read_input(file)
+        .unwrap_or_else(|e| fail(&format!("{}:{}:{}: {}", path, e.line, e.column, e.message)));
+    let mut intcode = IntCode::new(&program);
+    for value in inputs {
+        intcode.input.push(value);
+    }
+
+    let (report, trace, result) = profile(&mut intcode, trace, top);
+    print!("{}", report);
+    if let Some(mut trace) = trace {
+        if let Err(e) = trace.flush() {
+            eprintln!("Unable to write the trace: {}", e);
+        }
+    }
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+// Runs the program until it halts, waits for input or fails, and returns the report
+// along with the trace writer. A failed run still gets both, it's when they matter most
+fn profile<W: Write + Send + 'static>(
+    intcode: &mut IntCode,
+    trace: Option<W>,
+    top: usize,
+) -> (String, Option<W>, Result<(), IntcodeError>) {
+    let tracer = Arc::new(Mutex::new(ProfileTracer {
+        profiler: Profiler::new(),
+        trace: trace.map(WriterTracer::new),
+    }));
+    intcode.set_tracer(tracer.clone());
+    intcode.track_self_modification();
+
+    let mut report = String::new();
+    let result = intcode.run_until_blocked().map(|(outputs, state)| {
+        writeln!(report, "Outputs: {:?}", outputs).unwrap();
+        if state == RunState::AwaitingInput {
+            writeln!(report, "Stopped waiting for input").unwrap();
+        }
+    });
+    let mut tracer = tracer.lock().unwrap();
+    writeln!(report, "{}", tracer.profiler.report(top)).unwrap();
+
+    let sites = intcode.self_modification_sites();
+    if !sites.is_empty() {
+        writeln!(report, "Self-modifying writes:").unwrap();
+        for (writer, instruction) in sites {
+            let count = intcode
+                .self_modifications()
+                .iter()
+                .filter(|m| m.program_counter == writer && m.instruction == instruction)
+                .count();
+            writeln!(
+                report,
+                "  {:>6} patched {:>6} {} times",
+                writer, instruction, count
+            )
+            .unwrap();
+        }
+    }
+    let trace = tracer.trace.take().map(WriterTracer::into_inner);
+    (report, trace, result)
+}
+
+#[test]
+fn test_profile_error() {
+    // Outputs once, then jumps to an invalid opcode
+    let mut intcode = IntCode::new(&vec![104, 7, 1105, 1, 6, 0, 77]);
+    let (report, trace, result) = profile(&mut intcode, Some(Vec::new()), 5);
+    assert_eq!(result.unwrap_err().program_counter, 6);
+    assert!(report.starts_with("2 instructions executed"));
+    let trace = String::from_utf8(trace.unwrap()).unwrap();
+    assert_eq!(trace.lines().count(), 2);
+    assert!(trace.lines().last().unwrap().starts_with("0002 rb=0 JNZ"));
+}
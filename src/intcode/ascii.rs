@@ -0,0 +1,93 @@
+use crate::intcode::{
+    AsciiOutput, IntCode, IntcodeError, MemoryBackend, OutputSink, PagedMemory, RunState,
+};
+
+// Talks to programs that speak ASCII: lines of text in, text out, usually followed by a
+// single value too large to be a character once the puzzle is solved
+pub struct AsciiTerminal<M: MemoryBackend = PagedMemory> {
+    pub intcode: IntCode<M>,
+}
+
+impl<M: MemoryBackend> AsciiTerminal<M> {
+    pub fn new(intcode: IntCode<M>) -> AsciiTerminal<M> {
+        AsciiTerminal { intcode }
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        for c in line.bytes() {
+            self.intcode.input.push(c as i64);
+        }
+        self.intcode.input.push(10);
+    }
+
+    pub fn send_lines(&mut self, lines: &[&str]) {
+        for line in lines {
+            self.send_line(line);
+        }
+    }
+
+    // Reads until the text ends with `prompt`, the program needs input or it halts
+    pub fn read_until_prompt(&mut self, prompt: &str) -> Result<AsciiOutput, IntcodeError> {
+        let mut output = AsciiOutput::new();
+        loop {
+            match self.intcode.run()? {
+                RunState::Output(value) => {
+                    output.send(value);
+                    if !prompt.is_empty() && output.text.ends_with(prompt) {
+                        return Ok(output);
+                    }
+                }
+                _ => return Ok(output),
+            }
+        }
+    }
+
+    // Reads until the program needs input or halts
+    pub fn read_until_blocked(&mut self) -> Result<AsciiOutput, IntcodeError> {
+        self.read_until_prompt("")
+    }
+
+    pub fn halted(&self) -> bool {
+        self.intcode.finished
+    }
+}
+
+#[test]
+fn test_ascii_terminal() {
+    use crate::intcode::asm::assemble;
+
+    // Prompts for a line, then reports its length times 1000
+    let program = assemble(
+        "
+                OUT #62
+                OUT #32
+        read:   IN [char]
+                EQ [char], #10, [done]
+                JNZ [done], #report
+                ADD [count], #1, [count]
+                JZ #0, #read
+        report: OUT #111
+                OUT #107
+                OUT #10
+                MUL [count], #1000, [count]
+                OUT [count]
+                HLT
+        char:   DATA 0
+        done:   DATA 0
+        count:  DATA 0
+        ",
+    )
+    .unwrap();
+
+    let mut terminal = AsciiTerminal::new(IntCode::new(&program));
+    let prompt = terminal.read_until_prompt("> ").unwrap();
+    assert_eq!(prompt.text, "> ");
+    assert_eq!(prompt.result(), None);
+    assert_eq!(terminal.read_until_blocked().unwrap().text, "");
+
+    terminal.send_line("abc");
+    let output = terminal.read_until_prompt("never printed").unwrap();
+    assert_eq!(output.text, "ok\n");
+    assert_eq!(output.result(), Some(3000));
+    assert!(terminal.halted());
+}
@@ -0,0 +1,263 @@
+use crate::intcode::instruction::{Instruction, Mode, Opcode, Parameter};
+use core::fmt;
+use std::collections::HashMap;
+
+// Assembler for the mnemonic language printed by the disassembler:
+//
+//     loop:   IN [buf]            ; labels end with ':'
+//             JNZ [buf], #loop    ; [x] position, #x immediate, rb[x] relative
+//             HLT
+//     buf:    DATA 0, 0, 1        ; raw values, labels allowed
+//
+// Operands are integers or labels with an optional +/- offset. A numeric label such
+// as `0012:` is an address check rather than a definition, so listings round-trip.
+
+#[derive(Debug, PartialEq)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+#[derive(Clone, Debug)]
+enum Value {
+    Number(i64),
+    Label(String, i64),
+}
+
+enum Item {
+    Code(Opcode, Vec<(Mode, Value)>),
+    Data(Vec<Value>),
+}
+
+struct Statement {
+    line: usize,
+    item: Item,
+}
+
+pub fn assemble(source: &str) -> Result<Vec<i64>, AsmError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (index, raw) in source.lines().enumerate() {
+        let line = index + 1;
+        let error = |message: String| AsmError { line, message };
+        let mut text = raw.split(';').next().unwrap().trim();
+
+        while let Some(colon) = text.find(':') {
+            let name = text[..colon].trim();
+            if let Ok(expected) = name.parse::<usize>() {
+                if expected != address {
+                    return Err(error(format!(
+                        "address {} expected but this is {}",
+                        expected, address
+                    )));
+                }
+            } else if is_identifier(name) {
+                if labels.insert(name.to_string(), address as i64).is_some() {
+                    return Err(error(format!("label {} defined twice", name)));
+                }
+            } else {
+                return Err(error(format!("invalid label '{}'", name)));
+            }
+            text = text[colon + 1..].trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, rest) = match text.find(char::is_whitespace) {
+            Some(i) => (&text[..i], text[i..].trim()),
+            None => (text, ""),
+        };
+        let operands: Vec<&str> = if rest.is_empty() {
+            Vec::new()
+        } else {
+            rest.split(',').map(|o| o.trim()).collect()
+        };
+
+        let item = if mnemonic.eq_ignore_ascii_case("DATA") {
+            if operands.is_empty() {
+                return Err(error("DATA needs at least one value".to_string()));
+            }
+            let values = operands
+                .iter()
+                .map(|o| parse_value(o))
+                .collect::<Result<Vec<Value>, String>>()
+                .map_err(error)?;
+            address += values.len();
+            Item::Data(values)
+        } else {
+            let opcode = Opcode::from_mnemonic(mnemonic)
+                .ok_or_else(|| error(format!("unknown mnemonic {}", mnemonic)))?;
+            if operands.len() != opcode.parameter_count() {
+                return Err(error(format!(
+                    "{} takes {} operands, found {}",
+                    opcode.mnemonic(),
+                    opcode.parameter_count(),
+                    operands.len()
+                )));
+            }
+            let mut parameters = Vec::new();
+            for (i, operand) in operands.iter().enumerate() {
+                let (mode, value) = parse_operand(operand).map_err(error)?;
+                if mode == Mode::Immediate && opcode.write_parameter() == Some(i) {
+                    return Err(error(format!(
+                        "{} cannot write to an immediate operand",
+                        opcode.mnemonic()
+                    )));
+                }
+                parameters.push((mode, value));
+            }
+            address += opcode.parameter_count() + 1;
+            Item::Code(opcode, parameters)
+        };
+        statements.push(Statement { line, item });
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for statement in statements {
+        let resolve = |value: &Value| match value {
+            Value::Number(n) => Ok(*n),
+            Value::Label(name, offset) => match labels.get(name) {
+                Some(address) => Ok(address + offset),
+                None => Err(AsmError {
+                    line: statement.line,
+                    message: format!("undefined label {}", name),
+                }),
+            },
+        };
+        match &statement.item {
+            Item::Code(opcode, operands) => {
+                let mut parameters = Vec::new();
+                for (mode, value) in operands {
+                    parameters.push(Parameter {
+                        mode: *mode,
+                        value: resolve(value)?,
+                    });
+                }
+                let instruction = Instruction {
+                    opcode: *opcode,
+                    parameters,
+                };
+                program.extend(instruction.encode());
+            }
+            Item::Data(values) => {
+                for value in values {
+                    program.push(resolve(value)?);
+                }
+            }
+        }
+    }
+    Ok(program)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_operand(operand: &str) -> Result<(Mode, Value), String> {
+    let (mode, inner) = if let Some(rest) = operand.strip_prefix('#') {
+        (Mode::Immediate, rest)
+    } else if let Some(rest) = operand.strip_prefix("rb[") {
+        (Mode::Relative, strip_bracket(operand, rest)?)
+    } else if let Some(rest) = operand.strip_prefix('[') {
+        (Mode::Position, strip_bracket(operand, rest)?)
+    } else {
+        return Err(format!(
+            "operand '{}' needs a mode: [x], #x or rb[x]",
+            operand
+        ));
+    };
+    Ok((mode, parse_value(inner.trim())?))
+}
+
+fn strip_bracket<'a>(operand: &str, rest: &'a str) -> Result<&'a str, String> {
+    rest.strip_suffix(']')
+        .ok_or_else(|| format!("missing ']' in '{}'", operand))
+}
+
+fn parse_value(text: &str) -> Result<Value, String> {
+    if text.is_empty() {
+        return Err("missing value".to_string());
+    }
+    if let Ok(n) = text.parse::<i64>() {
+        return Ok(Value::Number(n));
+    }
+    let split = text
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == '+' || *c == '-')
+        .map(|(i, _)| i);
+    let (name, offset) = match split {
+        Some(i) => {
+            let offset = text[i..]
+                .replace(' ', "")
+                .parse::<i64>()
+                .map_err(|_| format!("invalid offset in '{}'", text))?;
+            (text[..i].trim(), offset)
+        }
+        None => (text, 0),
+    };
+    if !is_identifier(name) {
+        return Err(format!("invalid value '{}'", text));
+    }
+    Ok(Value::Label(name.to_string(), offset))
+}
+
+#[test]
+fn test_assemble() {
+    let program = assemble(
+        "
+        ; Outputs 1 if the input equals 8, 0 otherwise
+        start:  IN [value]
+                EQ [value], #8, [value]
+                OUT [value]
+                HLT
+        value:  DATA -1
+        ",
+    )
+    .unwrap();
+    assert_eq!(program, vec![3, 9, 1008, 9, 8, 9, 4, 9, 99, -1]);
+
+    let program = assemble("ARB #1\nOUT rb[-1]\nJZ #0, #end+1\nend: DATA 99, 99").unwrap();
+    assert_eq!(program, vec![109, 1, 204, -1, 1106, 0, 8, 99, 99]);
+}
+
+#[test]
+fn test_assemble_errors() {
+    assert_eq!(assemble("FOO #1").unwrap_err().line, 1);
+    assert_eq!(assemble("\nADD #1, #2").unwrap_err().line, 2);
+    assert_eq!(assemble("IN #1").unwrap_err().line, 1);
+    assert_eq!(assemble("OUT [nowhere]").unwrap_err().line, 1);
+    assert_eq!(assemble("a: HLT\na: HLT").unwrap_err().line, 2);
+    assert_eq!(assemble("HLT\n0002: HLT").unwrap_err().line, 2);
+    assert_eq!(assemble("OUT 5").unwrap_err().line, 1);
+}
+
+#[test]
+fn test_listing_round_trip() {
+    use crate::intcode::disasm::disassemble;
+
+    let program = vec![
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+    let listing = disassemble(&program).to_string();
+    assert_eq!(assemble(&listing).unwrap(), program);
+}
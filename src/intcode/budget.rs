@@ -0,0 +1,143 @@
+use crate::intcode::ErrorKind;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// Reading the clock costs more than an instruction, so deadlines are checked this often
+const DEADLINE_INTERVAL: u64 = 1024;
+
+// Computers add their cycles to the total this many at a time, so the shared counter isn't
+// touched on every instruction
+const REPORT_INTERVAL: u64 = 4096;
+
+static TOTAL_CYCLES: AtomicU64 = AtomicU64::new(0);
+
+// Instructions executed by every computer in the process, each counted once however many
+// times its computer was cloned. A computer that's still around may have up to
+// `REPORT_INTERVAL` instructions it hasn't added yet, the rest are added when it's dropped
+pub fn total_cycles() -> u64 {
+    TOTAL_CYCLES.load(Ordering::Relaxed)
+}
+
+// Limits on how long a computer may run before steps fail with `BudgetExhausted`. The
+// computer is left as it was, so raising the budget lets it carry on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Budget {
+    pub max_cycles: Option<u64>,
+    pub deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub fn cycles(max_cycles: u64) -> Budget {
+        Budget {
+            max_cycles: Some(max_cycles),
+            deadline: None,
+        }
+    }
+
+    pub fn timeout(timeout: Duration) -> Budget {
+        Budget {
+            max_cycles: None,
+            deadline: Some(Instant::now() + timeout),
+        }
+    }
+
+    pub(crate) fn check(&self, cycles: u64) -> Result<(), ErrorKind> {
+        if self.max_cycles.map_or(false, |max| cycles >= max) {
+            return Err(ErrorKind::BudgetExhausted);
+        }
+        if let Some(deadline) = self.deadline {
+            if cycles % DEADLINE_INTERVAL == 0 && Instant::now() >= deadline {
+                return Err(ErrorKind::BudgetExhausted);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Clones start with nothing to report, so instructions run before the clone are only
+// added to the total by the original
+#[derive(Default)]
+pub(crate) struct CycleCounter {
+    pub(crate) cycles: u64,
+    unreported: u64,
+}
+
+impl CycleCounter {
+    #[inline]
+    pub(crate) fn tick(&mut self) {
+        self.cycles += 1;
+        self.unreported += 1;
+        if self.unreported == REPORT_INTERVAL {
+            self.report();
+        }
+    }
+
+    fn report(&mut self) {
+        TOTAL_CYCLES.fetch_add(self.unreported, Ordering::Relaxed);
+        self.unreported = 0;
+    }
+}
+
+impl Clone for CycleCounter {
+    fn clone(&self) -> CycleCounter {
+        CycleCounter {
+            cycles: self.cycles,
+            unreported: 0,
+        }
+    }
+}
+
+impl Drop for CycleCounter {
+    fn drop(&mut self) {
+        self.report();
+    }
+}
+
+#[test]
+fn test_budget() {
+    use crate::intcode::compiled::CompiledIntCode;
+    use crate::intcode::{IntCode, MemoryBackend, RunState};
+
+    // Counts forever without reading or printing anything
+    let program = vec![1001, 7, 1, 7, 1105, 1, 0, 0];
+    let mut code = IntCode::new(&program);
+    code.set_budget(Budget::cycles(101));
+    let error = code.advance(&mut Vec::new()).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::BudgetExhausted);
+    assert_eq!(code.cycles(), 101);
+    assert_eq!(code.memory.read(7), Ok(51));
+
+    // Picks up where it stopped once there's more budget
+    code.set_budget(Budget::cycles(201));
+    assert!(code.run().is_err());
+    assert_eq!(code.memory.read(7), Ok(101));
+
+    let mut code = IntCode::new(&program);
+    code.set_budget(Budget::timeout(Duration::from_millis(20)));
+    let error = code.run().unwrap_err();
+    assert_eq!(error.kind, ErrorKind::BudgetExhausted);
+    assert!(code.cycles() > 0);
+
+    let mut compiled = IntCode::new(&program);
+    compiled.set_budget(Budget::cycles(101));
+    let mut compiled = CompiledIntCode::compile(compiled);
+    assert_eq!(compiled.run().unwrap_err().kind, ErrorKind::BudgetExhausted);
+    assert_eq!(compiled.intcode().cycles(), 101);
+
+    // Cycles run before a clone are counted once
+    let before = total_cycles();
+    let mut code = IntCode::new(&vec![104, 1, 99]);
+    assert_eq!(code.run(), Ok(RunState::Output(1)));
+    let mut copy = code.clone();
+    assert_eq!(copy.run(), Ok(RunState::Halted));
+    drop(code);
+    drop(copy);
+    assert!(total_cycles() >= before + 2);
+
+    // A computer that keeps running is counted before it's dropped
+    let before = total_cycles();
+    let mut code = IntCode::new(&program);
+    code.set_budget(Budget::cycles(3 * REPORT_INTERVAL));
+    assert!(code.run().is_err());
+    assert!(total_cycles() >= before + 3 * REPORT_INTERVAL);
+}
@@ -0,0 +1,361 @@
+use crate::intcode::disasm::label_name;
+use crate::intcode::instruction::{Instruction, Mode, Opcode, Parameter};
+use core::fmt::{self, Write};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// How control leaves a block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    // Runs straight into the next block
+    Fallthrough,
+    Jump,
+    // Conditional jump to an immediate target
+    Branch,
+    // Jump to a function after storing the return address in a relative slot
+    Call,
+    // Jump through a relative slot, the other half of the call idiom
+    Return,
+    // Jump through memory that doesn't look like a return
+    Indirect,
+    Halt,
+    // Runs into a cell that doesn't decode to a valid instruction
+    Invalid,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    Fallthrough,
+    Jump,
+    Call,
+    // From a call to the address it returns to
+    CallReturn,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    pub instructions: Vec<(usize, Instruction)>,
+    pub exit: Exit,
+}
+
+impl Block {
+    // First address past the block
+    pub fn end(&self) -> usize {
+        self.instructions
+            .last()
+            .map_or(self.start, |(a, i)| a + i.size())
+    }
+}
+
+pub struct ControlFlowGraph {
+    // Keyed by start address
+    pub blocks: BTreeMap<usize, Block>,
+    pub edges: Vec<Edge>,
+    // Entry points of everything reached through the call idiom
+    pub functions: BTreeSet<usize>,
+}
+
+// Where one instruction can send control next
+struct Flow {
+    exit: Option<Exit>,
+    targets: Vec<(usize, EdgeKind)>,
+}
+
+// Builds the graph by following control flow from address 0, so data is never decoded
+// as code. Only immediate jump targets are followed. A jump to an immediate target is a
+// call when the same straight-line run stored its return address into a relative slot,
+// which is how compiled Intcode programs pass return addresses. Jumps through a relative
+// slot are returns
+pub fn build_cfg(program: &[i64]) -> ControlFlowGraph {
+    let mut instructions: HashMap<usize, (Instruction, Flow)> = HashMap::new();
+    let mut leaders = BTreeSet::new();
+    let mut functions = BTreeSet::new();
+    let mut pending = vec![0];
+    leaders.insert(0);
+
+    while let Some(start) = pending.pop() {
+        let mut address = start;
+        // Immediate values stored into relative slots since the start of this run
+        let mut stored = Vec::new();
+        while address < program.len() && !instructions.contains_key(&address) {
+            let instruction = match Instruction::decode(&program[address..]) {
+                Some(instruction) => instruction,
+                None => break,
+            };
+            let next = address + instruction.size();
+            if let Some(value) = stored_constant(&instruction) {
+                stored.push(value);
+            }
+            let flow = flow(&instruction, next, &stored);
+            for (target, kind) in &flow.targets {
+                if *kind != EdgeKind::Fallthrough || flow.exit.is_some() {
+                    leaders.insert(*target);
+                    pending.push(*target);
+                }
+                if *kind == EdgeKind::Call {
+                    functions.insert(*target);
+                }
+            }
+            let ends = flow.exit.is_some();
+            instructions.insert(address, (instruction, flow));
+            if ends {
+                break;
+            }
+            address = next;
+            // Running into code decoded earlier splits it into a new block
+            if instructions.contains_key(&address) {
+                leaders.insert(address);
+            }
+        }
+    }
+
+    let mut blocks = BTreeMap::new();
+    let mut edges = Vec::new();
+    for start in &leaders {
+        if !instructions.contains_key(start) {
+            continue;
+        }
+        let mut block = Block {
+            start: *start,
+            instructions: Vec::new(),
+            exit: Exit::Invalid,
+        };
+        let mut address = *start;
+        while let Some((instruction, flow)) = instructions.get(&address) {
+            block.instructions.push((address, instruction.clone()));
+            address += instruction.size();
+            if let Some(exit) = flow.exit {
+                block.exit = exit;
+                for (to, kind) in &flow.targets {
+                    edges.push(Edge {
+                        from: *start,
+                        to: *to,
+                        kind: *kind,
+                    });
+                }
+                break;
+            }
+            if leaders.contains(&address) && instructions.contains_key(&address) {
+                block.exit = Exit::Fallthrough;
+                edges.push(Edge {
+                    from: *start,
+                    to: address,
+                    kind: EdgeKind::Fallthrough,
+                });
+                break;
+            }
+        }
+        blocks.insert(*start, block);
+    }
+
+    // Targets that didn't decode have no block to point at
+    edges.retain(|e| blocks.contains_key(&e.to));
+    ControlFlowGraph {
+        blocks,
+        edges,
+        functions,
+    }
+}
+
+// The value an ADD or MUL of two immediates writes into a relative slot
+fn stored_constant(instruction: &Instruction) -> Option<i64> {
+    let p = &instruction.parameters;
+    let constant = |p: &Parameter| (p.mode == Mode::Immediate).then_some(p.value);
+    let (a, b) = match instruction.opcode {
+        Opcode::Add | Opcode::Mul if p[2].mode == Mode::Relative => {
+            (constant(&p[0])?, constant(&p[1])?)
+        }
+        _ => return None,
+    };
+    Some(if instruction.opcode == Opcode::Add {
+        a + b
+    } else {
+        a * b
+    })
+}
+
+fn flow(instruction: &Instruction, next: usize, stored: &[i64]) -> Flow {
+    let exit = |exit, targets| Flow {
+        exit: Some(exit),
+        targets,
+    };
+    match instruction.opcode {
+        Opcode::Hlt => return exit(Exit::Halt, vec![]),
+        Opcode::Jnz | Opcode::Jz => (),
+        _ => {
+            return Flow {
+                exit: None,
+                targets: vec![(next, EdgeKind::Fallthrough)],
+            }
+        }
+    }
+
+    let (test, target) = (instruction.parameters[0], instruction.parameters[1]);
+    // An immediate test means the jump is either always or never taken
+    let taken = match test.mode {
+        Mode::Immediate => Some((test.value != 0) == (instruction.opcode == Opcode::Jnz)),
+        _ => None,
+    };
+    let fallthrough = vec![(next, EdgeKind::Fallthrough)];
+    if taken == Some(false) {
+        return Flow {
+            exit: None,
+            targets: fallthrough,
+        };
+    }
+    let always = taken == Some(true);
+    match target.mode {
+        Mode::Immediate if target.value >= 0 => {
+            let to = target.value as usize;
+            if always && stored.contains(&(next as i64)) {
+                exit(
+                    Exit::Call,
+                    vec![(to, EdgeKind::Call), (next, EdgeKind::CallReturn)],
+                )
+            } else if always {
+                exit(Exit::Jump, vec![(to, EdgeKind::Jump)])
+            } else {
+                exit(
+                    Exit::Branch,
+                    vec![(to, EdgeKind::Jump), (next, EdgeKind::Fallthrough)],
+                )
+            }
+        }
+        // A negative immediate target always faults
+        Mode::Immediate if always => exit(Exit::Invalid, vec![]),
+        Mode::Immediate => exit(Exit::Branch, fallthrough),
+        Mode::Relative => exit(Exit::Return, if always { vec![] } else { fallthrough }),
+        Mode::Position => exit(Exit::Indirect, if always { vec![] } else { fallthrough }),
+    }
+}
+
+impl ControlFlowGraph {
+    // Graphviz source, one box per block with its listing. Functions get a double border
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot).unwrap();
+        dot
+    }
+
+    fn write_dot(&self, f: &mut String) -> fmt::Result {
+        writeln!(f, "digraph cfg {{")?;
+        writeln!(f, "    node [shape=box, fontname=\"monospace\"];")?;
+        for block in self.blocks.values() {
+            let mut label = format!("{}:\\l", label_name(block.start));
+            for (address, instruction) in &block.instructions {
+                label.push_str(&format!("{:04}:   {}\\l", address, instruction));
+            }
+            let shape = if self.functions.contains(&block.start) {
+                ", peripheries=2"
+            } else {
+                ""
+            };
+            writeln!(
+                f,
+                "    {} [label=\"{}\"{}];",
+                label_name(block.start),
+                label,
+                shape
+            )?;
+        }
+        for edge in &self.edges {
+            let style = match edge.kind {
+                EdgeKind::Fallthrough => "",
+                EdgeKind::Jump => " [color=blue]",
+                EdgeKind::Call => " [label=\"call\", color=red]",
+                EdgeKind::CallReturn => " [style=dashed]",
+            };
+            writeln!(
+                f,
+                "    {} -> {}{};",
+                label_name(edge.from),
+                label_name(edge.to),
+                style
+            )?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[test]
+fn test_cfg() {
+    use crate::intcode::asm::assemble;
+
+    // Calls `double` twice, once per input, through the relative base calling convention
+    let program = assemble(
+        "
+                ARB #stack
+        loop:   IN [x]
+                JZ [x], #end
+                ADD #ret, #0, rb[0]
+                ADD [x], #0, rb[1]
+                JZ #0, #double
+        ret:    OUT [x]
+                JNZ #1, #loop
+        end:    HLT
+        double: ARB #2
+                MUL rb[-1], #2, [x]
+                ARB #-2
+                JZ #0, rb[0]
+        x:      DATA 0
+        stack:  DATA 0
+        ",
+    )
+    .unwrap();
+    let cfg = build_cfg(&program);
+
+    let starts: Vec<usize> = cfg.blocks.keys().cloned().collect();
+    assert_eq!(starts, vec![0, 2, 7, 18, 23, 24]);
+    let exits: Vec<Exit> = cfg.blocks.values().map(|b| b.exit).collect();
+    assert_eq!(
+        exits,
+        vec![
+            Exit::Fallthrough,
+            Exit::Branch,
+            Exit::Call,
+            Exit::Jump,
+            Exit::Halt,
+            Exit::Return
+        ]
+    );
+    assert_eq!(cfg.functions.iter().cloned().collect::<Vec<_>>(), vec![24]);
+    assert_eq!(cfg.blocks[&24].end(), 35);
+    assert!(cfg.edges.contains(&Edge {
+        from: 7,
+        to: 18,
+        kind: EdgeKind::CallReturn
+    }));
+    assert!(cfg.edges.contains(&Edge {
+        from: 18,
+        to: 2,
+        kind: EdgeKind::Jump
+    }));
+
+    let dot = cfg.to_dot();
+    assert!(dot.starts_with("digraph cfg {"));
+    assert!(dot.contains("L0007 -> L0024 [label=\"call\", color=red];"));
+    assert!(dot.contains("L0024 [label=\"L0024:\\l0024:   ARB #2\\l"));
+
+    // A jump after the halt is never reached so it leaves the code whole, a reachable jump
+    // into the middle of straight-line code splits it
+    let cfg = build_cfg(&[1101, 0, 0, 20, 1101, 0, 0, 20, 99, 1105, 1, 4]);
+    assert_eq!(cfg.blocks.len(), 1);
+    let cfg = build_cfg(&[1105, 1, 7, 1101, 0, 0, 20, 1101, 0, 0, 20, 1105, 1, 3]);
+    assert_eq!(
+        cfg.blocks.keys().cloned().collect::<Vec<_>>(),
+        vec![0, 3, 7]
+    );
+    assert_eq!(cfg.blocks[&3].exit, Exit::Fallthrough);
+
+    // Data after a halt is never decoded
+    let cfg = build_cfg(&[99, 1, 2, 3, 4]);
+    assert_eq!(cfg.blocks.len(), 1);
+    assert!(cfg.edges.is_empty());
+}
@@ -0,0 +1,229 @@
+use crate::intcode::IntInput;
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, Sender, SyncSender};
+
+// Where IN instructions read from, `None` means nothing is available yet and the
+// computer stops with `RunState::AwaitingInput`
+pub trait InputSource {
+    fn next_input(&mut self) -> Option<i64>;
+}
+
+// Where OUT instructions write to
+pub trait OutputSink {
+    fn send(&mut self, value: i64);
+}
+
+impl InputSource for IntInput {
+    fn next_input(&mut self) -> Option<i64> {
+        if self.has_input() {
+            Some(self.get())
+        } else {
+            None
+        }
+    }
+}
+
+impl OutputSink for IntInput {
+    fn send(&mut self, value: i64) {
+        self.push(value);
+    }
+}
+
+impl InputSource for VecDeque<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl OutputSink for VecDeque<i64> {
+    fn send(&mut self, value: i64) {
+        self.push_back(value);
+    }
+}
+
+impl OutputSink for Vec<i64> {
+    fn send(&mut self, value: i64) {
+        self.push(value);
+    }
+}
+
+impl<F: FnMut() -> Option<i64>> InputSource for F {
+    fn next_input(&mut self) -> Option<i64> {
+        self()
+    }
+}
+
+impl<F: FnMut(i64)> OutputSink for F {
+    fn send(&mut self, value: i64) {
+        self(value)
+    }
+}
+
+// Never blocks: waiting for a value is left to whoever drives the computer
+impl InputSource for Receiver<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.try_recv().ok()
+    }
+}
+
+// Values sent after the receiving end went away are dropped
+impl OutputSink for Sender<i64> {
+    fn send(&mut self, value: i64) {
+        let _ = Sender::send(self, value);
+    }
+}
+
+impl OutputSink for SyncSender<i64> {
+    fn send(&mut self, value: i64) {
+        let _ = SyncSender::send(self, value);
+    }
+}
+
+pub struct IterInput<I: Iterator<Item = i64>>(pub I);
+
+impl<I: Iterator<Item = i64>> InputSource for IterInput<I> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.0.next()
+    }
+}
+
+// Feeds text one character at a time, as the ASCII puzzles expect
+pub struct AsciiInput {
+    chars: VecDeque<i64>,
+}
+
+impl AsciiInput {
+    pub fn new(text: &str) -> AsciiInput {
+        AsciiInput {
+            chars: text.bytes().map(|b| b as i64).collect(),
+        }
+    }
+
+    // Each line is terminated by a newline
+    pub fn from_lines(lines: &[&str]) -> AsciiInput {
+        let mut input = AsciiInput::new("");
+        for line in lines {
+            input.push_line(line);
+        }
+        input
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        self.chars.extend(line.bytes().map(|b| b as i64));
+        self.chars.push_back(10);
+    }
+}
+
+impl InputSource for AsciiInput {
+    fn next_input(&mut self) -> Option<i64> {
+        self.chars.pop_front()
+    }
+}
+
+// Collects ASCII output as text, keeping anything outside the ASCII range apart
+#[derive(Default)]
+pub struct AsciiOutput {
+    pub text: String,
+    pub values: Vec<i64>,
+    last_was_value: bool,
+}
+
+impl AsciiOutput {
+    pub fn new() -> AsciiOutput {
+        AsciiOutput::default()
+    }
+
+    // The value printed last, if it wasn't a character
+    pub fn result(&self) -> Option<i64> {
+        if self.last_was_value {
+            self.values.last().copied()
+        } else {
+            None
+        }
+    }
+}
+
+impl OutputSink for AsciiOutput {
+    fn send(&mut self, value: i64) {
+        self.last_was_value = !(0..128).contains(&value);
+        if self.last_was_value {
+            self.values.push(value);
+        } else {
+            self.text.push(value as u8 as char);
+        }
+    }
+}
+
+// Answers with `value` whenever `source` runs dry, for programs that poll their input
+pub struct WithDefault<S: InputSource> {
+    pub source: S,
+    pub value: i64,
+}
+
+impl<S: InputSource> WithDefault<S> {
+    pub fn new(source: S, value: i64) -> WithDefault<S> {
+        WithDefault { source, value }
+    }
+}
+
+impl<S: InputSource> InputSource for WithDefault<S> {
+    fn next_input(&mut self) -> Option<i64> {
+        Some(self.source.next_input().unwrap_or(self.value))
+    }
+}
+
+#[test]
+fn test_sources_and_sinks() {
+    use crate::intcode::{IntCode, RunState};
+    use std::sync::mpsc::channel;
+
+    // Doubles every input
+    let program = vec![3, 9, 1002, 9, 2, 9, 4, 9, 1105, 1, 0];
+
+    let mut code = IntCode::new(&program);
+    let mut input: VecDeque<i64> = vec![1, 2].into_iter().collect();
+    let mut output = Vec::new();
+    assert_eq!(
+        code.run_with(&mut input, &mut output),
+        Ok(RunState::AwaitingInput)
+    );
+    assert_eq!(output, vec![2, 4]);
+
+    let mut code = IntCode::new(&program);
+    let mut values = vec![5, 6].into_iter();
+    let mut total = 0;
+    code.run_with(&mut || values.next(), &mut |v| total += v)
+        .unwrap();
+    assert_eq!(total, 22);
+
+    let mut code = IntCode::new(&program);
+    let mut output = VecDeque::new();
+    code.run_with(&mut IterInput(1..4), &mut output).unwrap();
+    assert_eq!(output, vec![2, 4, 6]);
+
+    let (in_tx, mut in_rx) = channel();
+    let (mut out_tx, out_rx) = channel();
+    in_tx.send(21).unwrap();
+    let mut code = IntCode::new(&program);
+    code.run_with(&mut in_rx, &mut out_tx).unwrap();
+    assert_eq!(out_rx.try_recv(), Ok(42));
+
+    // Polling an empty queue reads the default instead of blocking
+    let mut code = IntCode::new(&program);
+    let mut input = WithDefault::new(VecDeque::new(), -1);
+    let states: Vec<Option<RunState>> = (0..3)
+        .map(|_| code.step_from(&mut input).unwrap())
+        .collect();
+    assert_eq!(states, vec![None, None, Some(RunState::Output(-2))]);
+
+    // Echoes text back
+    let echo = vec![3, 7, 4, 7, 1105, 1, 0, 0];
+    let mut code = IntCode::new(&echo);
+    let mut text = AsciiInput::from_lines(&["hi", "there"]);
+    let mut output = AsciiOutput::new();
+    code.run_with(&mut text, &mut output).unwrap();
+    code.run_with(&mut IterInput(vec![1000].into_iter()), &mut output)
+        .unwrap();
+    assert_eq!(output.text, "hi\nthere\n");
+    assert_eq!(output.values, vec![1000]);
+}
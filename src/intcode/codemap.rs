@@ -0,0 +1,104 @@
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfModification {
+    // Address of the instruction doing the write
+    pub program_counter: i64,
+    pub address: i64,
+    // Start of the already executed instruction the write landed in
+    pub instruction: i64,
+    pub value: i64,
+}
+
+// Remembers which cells have run as part of an instruction, to catch writes into them
+#[derive(Clone, Default)]
+pub struct CodeMap {
+    starts: HashMap<i64, i64>,
+    events: Vec<SelfModification>,
+}
+
+impl CodeMap {
+    pub fn executed(&mut self, start: i64, size: i64) {
+        for address in start..start + size {
+            self.starts.insert(address, start);
+        }
+    }
+
+    pub fn written(&mut self, program_counter: i64, address: i64, value: i64) {
+        if let Some(instruction) = self.starts.get(&address) {
+            self.events.push(SelfModification {
+                program_counter,
+                address,
+                instruction: *instruction,
+                value,
+            });
+        }
+    }
+
+    pub fn events(&self) -> &[SelfModification] {
+        &self.events
+    }
+
+    pub fn take_events(&mut self) -> Vec<SelfModification> {
+        std::mem::take(&mut self.events)
+    }
+
+    // Every (writer, patched instruction) pair seen so far
+    pub fn sites(&self) -> BTreeSet<(i64, i64)> {
+        self.events
+            .iter()
+            .map(|e| (e.program_counter, e.instruction))
+            .collect()
+    }
+}
+
+#[test]
+fn test_self_modification() {
+    use crate::intcode::asm::assemble;
+    use crate::intcode::compiled::CompiledIntCode;
+    use crate::intcode::IntCode;
+
+    // Prints 5 the first time round the loop, then patches the OUT to print 7
+    let program = assemble(
+        "
+        loop:   OUT #5
+                ADD [n], #1, [n]
+                ADD #7, #0, [loop+1]
+                LT [n], #2, [t]
+                JNZ [t], #loop
+                HLT
+        n:      DATA 0
+        t:      DATA 0
+        ",
+    )
+    .unwrap();
+    let patch = SelfModification {
+        program_counter: 6,
+        address: 1,
+        instruction: 0,
+        value: 7,
+    };
+
+    let mut code = IntCode::new(&program);
+    code.track_self_modification();
+    assert_eq!(code.run_until_blocked().unwrap().0, vec![5, 7]);
+    assert_eq!(code.self_modifications(), &[patch, patch]);
+    assert_eq!(
+        code.self_modification_sites()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![(6, 0)]
+    );
+    assert_eq!(code.take_self_modifications().len(), 2);
+    assert!(code.self_modifications().is_empty());
+
+    let mut code = IntCode::new(&program);
+    code.track_self_modification();
+    let mut compiled = CompiledIntCode::compile(code);
+    assert_eq!(compiled.run_until_blocked().unwrap().0, vec![5, 7]);
+    assert_eq!(compiled.intcode().self_modifications(), &[patch, patch]);
+
+    let mut untracked = IntCode::new(&program);
+    untracked.run_until_blocked().unwrap();
+    assert!(untracked.self_modifications().is_empty());
+}
@@ -0,0 +1,354 @@
+use crate::intcode::instruction::{Instruction, Mode, Opcode, Parameter};
+use crate::intcode::{
+    ErrorKind, InputSource, IntCode, IntInput, IntcodeError, MemoryBackend, PagedMemory, RunState,
+};
+use std::sync::Arc;
+
+// Only the start of memory is pre-decoded, anything past it runs in the interpreter
+const CODE_LIMIT: i64 = 1 << 16;
+
+// What running one instruction did, and the address it wrote to
+type Effect = (Option<RunState>, Option<i64>);
+type OpFn<M> = dyn Fn(&mut IntCode<M>) -> Result<Effect, ErrorKind> + Send + Sync;
+
+struct Op<M: MemoryBackend> {
+    size: i64,
+    run: Box<OpFn<M>>,
+}
+
+enum Slot<M: MemoryBackend> {
+    // Not a valid instruction when the program was compiled
+    Empty,
+    Compiled(Arc<Op<M>>),
+    // Overwritten by the program, left to the interpreter from then on
+    Interpreted,
+}
+
+impl<M: MemoryBackend> Clone for Slot<M> {
+    fn clone(&self) -> Slot<M> {
+        match self {
+            Slot::Empty => Slot::Empty,
+            Slot::Compiled(op) => Slot::Compiled(op.clone()),
+            Slot::Interpreted => Slot::Interpreted,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Immediate(i64),
+    Position(i64),
+    Relative(i64),
+}
+
+impl Operand {
+    fn new(parameter: &Parameter) -> Operand {
+        match parameter.mode {
+            Mode::Immediate => Operand::Immediate(parameter.value),
+            Mode::Position => Operand::Position(parameter.value),
+            Mode::Relative => Operand::Relative(parameter.value),
+        }
+    }
+
+    #[inline]
+    fn read<M: MemoryBackend>(self, intcode: &IntCode<M>) -> Result<i64, ErrorKind> {
+        match self {
+            Operand::Immediate(value) => Ok(value),
+            Operand::Position(address) => intcode.memory.read(address),
+            Operand::Relative(offset) => intcode.memory.read(intcode.relative_base + offset),
+        }
+    }
+
+    // Decoding rejects immediate writes, so they never get here
+    #[inline]
+    fn address<M: MemoryBackend>(self, intcode: &IntCode<M>) -> i64 {
+        match self {
+            Operand::Position(address) => address,
+            Operand::Relative(offset) => intcode.relative_base + offset,
+            Operand::Immediate(_) => unreachable!(),
+        }
+    }
+}
+
+// Every address holding a valid instruction is decoded once up front into a closure with
+// its modes and operands already resolved. Clones share the decoded code until one of them
+// overwrites it, at which point that copy falls back to the interpreter for those addresses
+#[derive(Clone)]
+pub struct CompiledIntCode<M: MemoryBackend = PagedMemory> {
+    intcode: IntCode<M>,
+    code: Arc<Vec<Slot<M>>>,
+}
+
+impl CompiledIntCode {
+    pub fn new(program: &[i64]) -> CompiledIntCode {
+        CompiledIntCode::compile(IntCode::with_backend(program))
+    }
+}
+
+impl<M: MemoryBackend + 'static> CompiledIntCode<M> {
+    pub fn compile(intcode: IntCode<M>) -> CompiledIntCode<M> {
+        let end = intcode
+            .memory
+            .cells()
+            .last()
+            .map_or(0, |(address, _)| address + 1)
+            .min(CODE_LIMIT);
+        let code = (0..end)
+            .map(|address| match intcode.instruction_at(address) {
+                Some(instruction) => Slot::Compiled(Arc::new(compile_op(&instruction, address))),
+                None => Slot::Empty,
+            })
+            .collect();
+        CompiledIntCode {
+            intcode,
+            code: Arc::new(code),
+        }
+    }
+
+    pub fn intcode(&self) -> &IntCode<M> {
+        &self.intcode
+    }
+
+    pub fn into_intcode(self) -> IntCode<M> {
+        self.intcode
+    }
+
+    pub fn input(&mut self) -> &mut IntInput {
+        &mut self.intcode.input
+    }
+
+    // Writes through here keep the decoded code in sync, unlike writes to `intcode().memory`
+    pub fn store(&mut self, address: i64, value: i64) -> Result<(), ErrorKind> {
+        self.intcode.memory.store(address, value)?;
+        self.invalidate(address);
+        Ok(())
+    }
+
+    // Addresses whose decoded instruction was overwritten and now run in the interpreter
+    pub fn interpreted(&self) -> Vec<i64> {
+        (0..self.code.len())
+            .filter(|a| matches!(self.code[*a], Slot::Interpreted))
+            .map(|a| a as i64)
+            .collect()
+    }
+
+    pub fn step_state(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let program_counter = self.intcode.program_counter;
+        let op = match self.code.get(program_counter as usize) {
+            // Tracing and the error for running past a halt are the interpreter's job
+            Some(Slot::Compiled(op)) if !self.intcode.finished && self.intcode.tracer.is_none() => {
+                op
+            }
+            _ => return self.interpret(),
+        };
+        self.intcode.check_budget()?;
+        let (state, write) = (op.run)(&mut self.intcode).map_err(|kind| IntcodeError {
+            program_counter,
+            instruction: self.intcode.memory.read(program_counter).unwrap_or(0),
+            kind,
+        })?;
+        if state != Some(RunState::AwaitingInput) {
+            self.intcode.counter.tick();
+        }
+        if let Some(code_map) = &mut self.intcode.code_map {
+            if state != Some(RunState::AwaitingInput) {
+                code_map.executed(program_counter, op.size);
+                if let Some(address) = write {
+                    let value = self.intcode.memory.read(address).unwrap_or(0);
+                    code_map.written(program_counter, address, value);
+                }
+            }
+        }
+        if let Some(address) = write {
+            self.invalidate(address);
+        }
+        Ok(state)
+    }
+
+    fn interpret(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let write = self.intcode.write_address();
+        let state = self.intcode.step_state()?;
+        if let (Some(address), false) = (write, state == Some(RunState::AwaitingInput)) {
+            self.invalidate(address);
+        }
+        Ok(state)
+    }
+
+    // Drops every decoded instruction that overlaps `address`
+    fn invalidate(&mut self, address: i64) {
+        if address >= self.code.len() as i64 {
+            return;
+        }
+        for start in (address - 3).max(0)..=address {
+            if let Slot::Compiled(op) = &self.code[start as usize] {
+                if start + op.size > address {
+                    Arc::make_mut(&mut self.code)[start as usize] = Slot::Interpreted;
+                }
+            }
+        }
+    }
+
+    // Same as `IntCode::run`
+    pub fn run(&mut self) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step_state()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    // Same as `IntCode::run_until_blocked`
+    pub fn run_until_blocked(&mut self) -> Result<(Vec<i64>, RunState), IntcodeError> {
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                RunState::Output(value) => outputs.push(value),
+                state => return Ok((outputs, state)),
+            }
+        }
+    }
+}
+
+fn compile_op<M: MemoryBackend + 'static>(instruction: &Instruction, address: i64) -> Op<M> {
+    let size = instruction.size() as i64;
+    let next = address + size;
+    let operands: Vec<Operand> = instruction.parameters.iter().map(Operand::new).collect();
+    let run: Box<OpFn<M>> = match instruction.opcode {
+        Opcode::Add => arithmetic(&operands, next, |a, b| a + b),
+        Opcode::Mul => arithmetic(&operands, next, |a, b| a * b),
+        Opcode::Lt => arithmetic(&operands, next, |a, b| (a < b) as i64),
+        Opcode::Eq => arithmetic(&operands, next, |a, b| (a == b) as i64),
+        Opcode::In => {
+            let target = operands[0];
+            Box::new(move |intcode| {
+                let address = target.address(intcode);
+                match intcode.input.next_input() {
+                    Some(value) => {
+                        intcode.memory.store(address, value)?;
+                        intcode.program_counter = next;
+                        Ok((None, Some(address)))
+                    }
+                    None => Ok((Some(RunState::AwaitingInput), None)),
+                }
+            })
+        }
+        Opcode::Out => {
+            let source = operands[0];
+            Box::new(move |intcode| {
+                let value = source.read(intcode)?;
+                intcode.last_output = value;
+                intcode.program_counter = next;
+                Ok((Some(RunState::Output(value)), None))
+            })
+        }
+        Opcode::Jnz | Opcode::Jz => {
+            let (test, target) = (operands[0], operands[1]);
+            let jump_if_zero = instruction.opcode == Opcode::Jz;
+            Box::new(move |intcode| {
+                let value = test.read(intcode)?;
+                let target = target.read(intcode)?;
+                intcode.program_counter = if (value == 0) == jump_if_zero {
+                    target
+                } else {
+                    next
+                };
+                Ok((None, None))
+            })
+        }
+        Opcode::Arb => {
+            let offset = operands[0];
+            Box::new(move |intcode| {
+                intcode.relative_base += offset.read(intcode)?;
+                intcode.program_counter = next;
+                Ok((None, None))
+            })
+        }
+        Opcode::Hlt => Box::new(|intcode| {
+            intcode.finished = true;
+            Ok((Some(RunState::Halted), None))
+        }),
+    };
+    Op { size, run }
+}
+
+fn arithmetic<M: MemoryBackend + 'static>(
+    operands: &[Operand],
+    next: i64,
+    f: fn(i64, i64) -> i64,
+) -> Box<OpFn<M>> {
+    let (a, b, target) = (operands[0], operands[1], operands[2]);
+    Box::new(move |intcode| {
+        let address = target.address(intcode);
+        let value = f(a.read(intcode)?, b.read(intcode)?);
+        intcode.memory.store(address, value)?;
+        intcode.program_counter = next;
+        Ok((None, Some(address)))
+    })
+}
+
+#[test]
+fn test_compiled() {
+    use crate::intcode::asm::assemble;
+
+    // Reads a number and prints every value from it down to 1
+    let program = assemble(
+        "
+                IN [n]
+        loop:   OUT [n]
+                ADD [n], #-1, [n]
+                JNZ [n], #loop
+                HLT
+        n:      DATA 0
+        ",
+    )
+    .unwrap();
+    let mut compiled = CompiledIntCode::new(&program);
+    assert_eq!(compiled.run(), Ok(RunState::AwaitingInput));
+    compiled.input().push(4);
+    let mut interpreted = IntCode::new(&program);
+    interpreted.input.push(4);
+    assert_eq!(
+        compiled.run_until_blocked(),
+        interpreted.run_until_blocked()
+    );
+    assert!(compiled.intcode().finished);
+    assert!(compiled.interpreted().is_empty());
+    assert_eq!(compiled.step_state().unwrap_err().kind, ErrorKind::Halted);
+
+    let mut compiled = CompiledIntCode::new(&[1101, 1, 1, 5, 42, 0]);
+    let error = compiled.run().unwrap_err();
+    assert_eq!(
+        (error.program_counter, error.kind),
+        (4, ErrorKind::InvalidOpcode(42))
+    );
+}
+
+#[test]
+fn test_compiled_self_modification() {
+    use crate::intcode::asm::assemble;
+
+    // Patches the OUT below into an ARB before reaching it, so nothing is printed
+    let program = assemble(
+        "
+                ADD #9, #0, [patch]
+        patch:  OUT #7
+                OUT #8
+                HLT
+        ",
+    )
+    .unwrap();
+    let mut compiled = CompiledIntCode::new(&program);
+    let shared = compiled.clone();
+    assert_eq!(
+        compiled.run_until_blocked(),
+        Ok((vec![8], RunState::Halted))
+    );
+    assert_eq!(compiled.interpreted(), vec![3, 4]);
+    assert!(shared.interpreted().is_empty());
+
+    // Writes from outside are tracked the same way
+    let mut compiled = shared.clone();
+    compiled.store(0, 99).unwrap();
+    assert_eq!(compiled.run_until_blocked(), Ok((vec![], RunState::Halted)));
+    assert_eq!(compiled.interpreted(), vec![0]);
+}
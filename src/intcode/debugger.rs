@@ -0,0 +1,336 @@
+use crate::intcode::{IntCode, MemoryBackend, RunState};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Instructions `c` runs before giving control back, so a program that never stops can't
+// hang the debugger
+const CONTINUE_LIMIT: i64 = 10_000_000;
+
+const HELP: &str = "Commands:
+  s [n]            step n instructions (default 1)
+  c [n]            continue until a breakpoint, watchpoint, input request or halt,
+                   or for at most n instructions (default 10000000)
+  b [addr]         set a breakpoint, or list them
+  d <addr>         delete a breakpoint
+  w [addr]         watch a memory cell for changes, or list watchpoints
+  uw <addr>        remove a watchpoint
+  r                show registers and the input queue
+  m <addr> [n]     show n memory cells (default 8)
+  l [n]            disassemble n instructions from the program counter (default 5)
+  i <v>[,<v>...]   queue input values
+  a <text>         queue a line of ASCII input
+  save <file>      save the machine state
+  load <file>      load a machine state
+  q                quit";
+
+pub struct Debugger {
+    pub intcode: IntCode,
+    breakpoints: BTreeSet<i64>,
+    watchpoints: BTreeMap<i64, i64>,
+}
+
+enum Stop {
+    Running,
+    Paused(String),
+}
+
+impl Debugger {
+    pub fn new(intcode: IntCode) -> Debugger {
+        Debugger {
+            intcode,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+        }
+    }
+
+    // Runs one command line and returns the text to show the user
+    pub fn execute(&mut self, command: &str) -> String {
+        let mut parts = command.split_whitespace();
+        let name = parts.next().unwrap_or("");
+        let args: Vec<&str> = parts.collect();
+        let rest = command.trim()[name.len()..].trim();
+
+        match (name, args.len()) {
+            ("s", _) | ("step", _) => match number_arg(&args, 0, 1) {
+                Ok(count) => self.step(count),
+                Err(e) => e,
+            },
+            ("c", _) | ("continue", _) => match number_arg(&args, 0, CONTINUE_LIMIT) {
+                Ok(limit) => self.cont(limit),
+                Err(e) => e,
+            },
+            ("b", 0) | ("break", 0) => list("Breakpoints", self.breakpoints.iter()),
+            ("b", 1) | ("break", 1) => match number_arg(&args, 0, 0) {
+                Ok(address) => {
+                    self.breakpoints.insert(address);
+                    format!("Breakpoint at {:04}", address)
+                }
+                Err(e) => e,
+            },
+            ("d", 1) | ("delete", 1) => match number_arg(&args, 0, 0) {
+                Ok(address) if self.breakpoints.remove(&address) => {
+                    format!("Deleted breakpoint at {:04}", address)
+                }
+                Ok(address) => format!("No breakpoint at {:04}", address),
+                Err(e) => e,
+            },
+            ("w", 0) | ("watch", 0) => list("Watchpoints", self.watchpoints.keys()),
+            ("w", 1) | ("watch", 1) => match number_arg(&args, 0, 0) {
+                Ok(address) => {
+                    let value = self.read(address);
+                    self.watchpoints.insert(address, value);
+                    format!("Watching [{}] = {}", address, value)
+                }
+                Err(e) => e,
+            },
+            ("uw", 1) | ("unwatch", 1) => match number_arg(&args, 0, 0) {
+                Ok(address) if self.watchpoints.remove(&address).is_some() => {
+                    format!("Stopped watching [{}]", address)
+                }
+                Ok(address) => format!("Not watching [{}]", address),
+                Err(e) => e,
+            },
+            ("r", 0) | ("regs", 0) => self.registers(),
+            ("m", 1) | ("m", 2) | ("mem", 1) | ("mem", 2) => {
+                match (number_arg(&args, 0, 0), number_arg(&args, 1, 8)) {
+                    (Ok(address), Ok(count)) => self.dump(address, count),
+                    (Err(e), _) | (_, Err(e)) => e,
+                }
+            }
+            ("l", _) | ("list", _) => match number_arg(&args, 0, 5) {
+                Ok(count) => self.listing(count),
+                Err(e) => e,
+            },
+            ("i", _) | ("input", _) if !rest.is_empty() => {
+                let values: Result<Vec<i64>, _> =
+                    rest.split(',').map(|v| v.trim().parse::<i64>()).collect();
+                match values {
+                    Ok(values) => {
+                        for value in &values {
+                            self.intcode.input.push(*value);
+                        }
+                        format!("Queued {} values", values.len())
+                    }
+                    Err(_) => format!("Invalid input values: {}", rest),
+                }
+            }
+            ("a", _) | ("ascii", _) => {
+                for c in rest.chars() {
+                    self.intcode.input.push(c as i64);
+                }
+                self.intcode.input.push(10);
+                format!("Queued {} characters", rest.chars().count() + 1)
+            }
+            ("save", 1) => match self.intcode.save_to_file(args[0]) {
+                Ok(_) => format!("Saved to {}", args[0]),
+                Err(e) => format!("Unable to save {}: {}", args[0], e),
+            },
+            ("load", 1) => match IntCode::load_from_file(args[0]) {
+                Ok(intcode) => {
+                    self.intcode = intcode;
+                    format!("Loaded {}\n{}", args[0], self.current())
+                }
+                Err(e) => format!("Unable to load {}: {}", args[0], e),
+            },
+            ("h", _) | ("help", _) => HELP.to_string(),
+            _ => format!("Unknown command '{}', try 'h'", command.trim()),
+        }
+    }
+
+    fn read(&self, address: i64) -> i64 {
+        self.intcode.memory.read(address).unwrap_or(0)
+    }
+
+    // The instruction about to run, with its address
+    pub fn current(&self) -> String {
+        let pc = self.intcode.program_counter();
+        match self.intcode.instruction_at(pc) {
+            Some(instruction) => format!("{:04}:   {}", pc, instruction),
+            None => format!("{:04}:   ?? {}", pc, self.read(pc)),
+        }
+    }
+
+    fn step(&mut self, count: i64) -> String {
+        let mut lines = Vec::new();
+        for _ in 0..count {
+            lines.push(self.current());
+            if let Stop::Paused(reason) = self.single_step(&mut lines) {
+                lines.push(reason);
+                break;
+            }
+        }
+        lines.push(format!("-> {}", self.current()));
+        lines.join("\n")
+    }
+
+    fn cont(&mut self, limit: i64) -> String {
+        let mut lines = Vec::new();
+        for executed in 0.. {
+            if executed == limit {
+                lines.push(format!("Stopped after {} instructions", limit));
+                break;
+            }
+            if let Stop::Paused(reason) = self.single_step(&mut lines) {
+                lines.push(reason);
+                break;
+            }
+            let pc = self.intcode.program_counter();
+            if self.breakpoints.contains(&pc) {
+                lines.push(format!("Breakpoint at {:04}", pc));
+                break;
+            }
+        }
+        lines.push(format!("-> {}", self.current()));
+        lines.join("\n")
+    }
+
+    // Executes one instruction, collecting outputs and watchpoint hits into `lines`
+    fn single_step(&mut self, lines: &mut Vec<String>) -> Stop {
+        if self.intcode.finished {
+            return Stop::Paused("Program has halted".to_string());
+        }
+        let mut stop = Stop::Running;
+        match self.intcode.step_state() {
+            Ok(None) => (),
+            Ok(Some(RunState::Output(value))) => lines.push(format!("Output: {}", value)),
+            Ok(Some(RunState::AwaitingInput)) => {
+                stop = Stop::Paused("Waiting for input".to_string())
+            }
+            Ok(Some(RunState::Halted)) => stop = Stop::Paused("Program halted".to_string()),
+            Err(e) => stop = Stop::Paused(format!("Error: {}", e)),
+        }
+
+        let changes: Vec<(i64, i64, i64)> = self
+            .watchpoints
+            .iter()
+            .map(|(address, old)| (*address, *old, self.read(*address)))
+            .filter(|(_, old, new)| old != new)
+            .collect();
+        for (address, old, new) in changes {
+            self.watchpoints.insert(address, new);
+            lines.push(format!("Watch [{}]: {} -> {}", address, old, new));
+            stop = Stop::Paused("Watchpoint hit".to_string());
+        }
+        stop
+    }
+
+    fn registers(&self) -> String {
+        format!(
+            "pc {}\nrb {}\nlast output {}\nfinished {}\ninput [{}]",
+            self.intcode.program_counter(),
+            self.intcode.relative_base(),
+            self.intcode.last_output,
+            self.intcode.finished,
+            self.intcode
+                .input
+                .pending()
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    fn dump(&self, address: i64, count: i64) -> String {
+        (address..address + count)
+            .map(|a| format!("[{}] = {}", a, self.read(a)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn listing(&self, count: i64) -> String {
+        let mut address = self.intcode.program_counter();
+        let mut lines = Vec::new();
+        for _ in 0..count {
+            match self.intcode.instruction_at(address) {
+                Some(instruction) => {
+                    lines.push(format!("{:04}:   {}", address, instruction));
+                    address += instruction.size() as i64;
+                }
+                None => {
+                    lines.push(format!("{:04}:   DATA {}", address, self.read(address)));
+                    address += 1;
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+fn number_arg(args: &[&str], index: usize, default: i64) -> Result<i64, String> {
+    match args.get(index) {
+        Some(arg) => arg
+            .parse()
+            .map_err(|_| format!("Expected a number, found '{}'", arg)),
+        None => Ok(default),
+    }
+}
+
+fn list<'a, I: Iterator<Item = &'a i64>>(title: &str, addresses: I) -> String {
+    let addresses: Vec<String> = addresses.map(|a| format!("{:04}", a)).collect();
+    if addresses.is_empty() {
+        format!("{}: none", title)
+    } else {
+        format!("{}: {}", title, addresses.join(", "))
+    }
+}
+
+#[test]
+fn test_debugger() {
+    use crate::intcode::asm::assemble;
+
+    let program = assemble(
+        "
+        loop:   IN [value]
+                MUL [value], #2, [value]
+                OUT [value]
+                JZ #0, #loop
+        value:  DATA 0
+        ",
+    )
+    .unwrap();
+    let mut debugger = Debugger::new(IntCode::new(&program));
+
+    assert_eq!(
+        debugger.execute("l 2"),
+        "0000:   IN [11]\n0002:   MUL [11], #2, [11]"
+    );
+    assert_eq!(
+        debugger.execute("s"),
+        "0000:   IN [11]\nWaiting for input\n-> 0000:   IN [11]"
+    );
+    assert_eq!(debugger.execute("i 4, 5"), "Queued 2 values");
+    assert_eq!(debugger.execute("b 6"), "Breakpoint at 0006");
+    assert_eq!(
+        debugger.execute("c"),
+        "Breakpoint at 0006\n-> 0006:   OUT [11]"
+    );
+    assert_eq!(debugger.execute("m 11 1"), "[11] = 8");
+    assert_eq!(
+        debugger.execute("s 2"),
+        "0006:   OUT [11]\nOutput: 8\n0008:   JZ #0, #0\n-> 0000:   IN [11]"
+    );
+    assert_eq!(debugger.execute("w 11"), "Watching [11] = 8");
+    assert_eq!(
+        debugger.execute("c"),
+        "Watch [11]: 8 -> 5\nWatchpoint hit\n-> 0002:   MUL [11], #2, [11]"
+    );
+    assert_eq!(
+        debugger.execute("r"),
+        "pc 2\nrb 0\nlast output 8\nfinished false\ninput []"
+    );
+    assert_eq!(debugger.execute("d 6"), "Deleted breakpoint at 0006");
+    assert_eq!(debugger.execute("uw 11"), "Stopped watching [11]");
+    assert_eq!(
+        debugger.execute("c"),
+        "Output: 10\nWaiting for input\n-> 0000:   IN [11]"
+    );
+    assert_eq!(debugger.execute("m x"), "Expected a number, found 'x'");
+    assert_eq!(debugger.execute("foo"), "Unknown command 'foo', try 'h'");
+    assert_eq!(debugger.execute("a héllo"), "Queued 6 characters");
+
+    let mut debugger = Debugger::new(IntCode::new(&assemble("loop: JZ #0, #loop").unwrap()));
+    assert_eq!(
+        debugger.execute("c 100"),
+        "Stopped after 100 instructions\n-> 0000:   JZ #0, #0"
+    );
+}
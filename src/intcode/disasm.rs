@@ -0,0 +1,162 @@
+use crate::intcode::instruction::{Instruction, Mode};
+use core::fmt;
+use std::collections::HashSet;
+
+const DATA_PER_LINE: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Line {
+    Code(Instruction),
+    Data(Vec<i64>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub address: usize,
+    pub line: Line,
+}
+
+pub struct Listing {
+    pub entries: Vec<Entry>,
+    pub labels: HashSet<usize>,
+}
+
+pub fn label_name(address: usize) -> String {
+    format!("L{:04}", address)
+}
+
+// Linear sweep over the program. Cells that don't decode to a valid instruction are
+// grouped into DATA lines, and immediate jump targets that start a line get a label.
+pub fn disassemble(program: &[i64]) -> Listing {
+    let mut decoded = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        match Instruction::decode(&program[address..]) {
+            Some(instruction) => {
+                let size = instruction.size();
+                decoded.push((address, Some(instruction)));
+                address += size;
+            }
+            None => {
+                decoded.push((address, None));
+                address += 1;
+            }
+        }
+    }
+
+    let starts: HashSet<usize> = decoded.iter().map(|(a, _)| *a).collect();
+    let labels: HashSet<usize> = decoded
+        .iter()
+        .filter_map(|(_, i)| i.as_ref())
+        .filter_map(jump_target)
+        .filter(|t| starts.contains(t))
+        .collect();
+
+    let mut entries: Vec<Entry> = Vec::new();
+    for (address, instruction) in decoded {
+        match instruction {
+            Some(instruction) => entries.push(Entry {
+                address,
+                line: Line::Code(instruction),
+            }),
+            None => {
+                if let Some(Entry {
+                    line: Line::Data(values),
+                    ..
+                }) = entries.last_mut()
+                {
+                    if values.len() < DATA_PER_LINE && !labels.contains(&address) {
+                        values.push(program[address]);
+                        continue;
+                    }
+                }
+                entries.push(Entry {
+                    address,
+                    line: Line::Data(vec![program[address]]),
+                });
+            }
+        }
+    }
+
+    Listing { entries, labels }
+}
+
+fn jump_target(instruction: &Instruction) -> Option<usize> {
+    if !instruction.opcode.is_jump() {
+        return None;
+    }
+    let target = instruction.parameters[1];
+    if target.mode != Mode::Immediate || target.value < 0 {
+        return None;
+    }
+    Some(target.value as usize)
+}
+
+impl Listing {
+    fn format_code(&self, instruction: &Instruction) -> String {
+        let target = jump_target(instruction).filter(|t| self.labels.contains(t));
+        let mut buffer = instruction.opcode.mnemonic().to_string();
+        for (i, p) in instruction.parameters.iter().enumerate() {
+            buffer.push_str(if i == 0 { " " } else { ", " });
+            match target {
+                Some(t) if i == 1 => buffer.push_str(&format!("#{}", label_name(t))),
+                _ => buffer.push_str(&p.to_string()),
+            }
+        }
+        buffer
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            if self.labels.contains(&entry.address) {
+                writeln!(f, "{}:", label_name(entry.address))?;
+            }
+            let text = match &entry.line {
+                Line::Code(instruction) => self.format_code(instruction),
+                Line::Data(values) => format!(
+                    "DATA {}",
+                    values
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+            };
+            writeln!(f, "{:04}:   {}", entry.address, text)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_disassemble() {
+    let program = vec![3, 12, 1008, 12, 8, 13, 1005, 13, 0, 4, 12, 99, -1, 8, 12345];
+    let listing = disassemble(&program);
+    assert_eq!(listing.entries.len(), 6);
+    assert!(listing.labels.contains(&0));
+    assert_eq!(
+        listing.to_string(),
+        "L0000:
+0000:   IN [12]
+0002:   EQ [12], #8, [13]
+0006:   JNZ [13], #L0000
+0009:   OUT [12]
+0011:   HLT
+0012:   DATA -1, 8, 12345
+"
+    );
+}
+
+#[test]
+fn test_disassemble_data_groups() {
+    let program: Vec<i64> = vec![99, -5, -6, -7, -8, -9, -10, -11, -12, -13];
+    let listing = disassemble(&program);
+    assert_eq!(listing.entries.len(), 3);
+    assert_eq!(
+        listing.entries[1].line,
+        Line::Data(vec![-5, -6, -7, -8, -9, -10, -11, -12])
+    );
+    assert_eq!(listing.entries[2].address, 9);
+}
@@ -0,0 +1,47 @@
+use core::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    InvalidOpcode(i64),
+    InvalidMode(i64),
+    ImmediateWrite,
+    NegativeAddress(i64),
+    Halted,
+    // Ran out of cycles or time, see `Budget`
+    BudgetExhausted,
+    // A custom opcode stored a value without declaring a write parameter
+    NoWriteParameter,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::InvalidOpcode(opcode) => write!(f, "invalid opcode {}", opcode),
+            ErrorKind::InvalidMode(mode) => write!(f, "invalid parameter mode {}", mode),
+            ErrorKind::ImmediateWrite => write!(f, "write to an immediate parameter"),
+            ErrorKind::NegativeAddress(address) => write!(f, "negative address {}", address),
+            ErrorKind::Halted => write!(f, "computer called after having finished"),
+            ErrorKind::BudgetExhausted => write!(f, "execution budget exhausted"),
+            ErrorKind::NoWriteParameter => write!(f, "store without a write parameter"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntcodeError {
+    pub program_counter: i64,
+    pub instruction: i64,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at pc {} (instruction {})",
+            self.kind, self.program_counter, self.instruction
+        )
+    }
+}
+
+impl std::error::Error for IntcodeError {}
@@ -0,0 +1,279 @@
+use crate::intcode::compiled::CompiledIntCode;
+use crate::intcode::instruction::{Instruction, Mode, Opcode, Parameter};
+use crate::intcode::{HashMemory, IntCode, IntcodeError, MemoryBackend, PagedMemory, RunState};
+
+// Steps each run gets before it's cut short, generated programs loop freely
+const STEP_LIMIT: usize = 2000;
+const DATA_SIZE: usize = 16;
+// Well past the dense part of the paged memory
+const FAR_ADDRESS: i64 = (1 << 21) + 7;
+
+// xorshift64*, plenty for picking opcodes and saves a dependency
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // Uniform in low..high
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next_u64() % (high - low) as u64) as i64
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+}
+
+// A random program where every instruction decodes, followed by some data. Operands
+// mostly point at the data, but some land in the code, far away or through the
+// relative base, and some jumps go through memory
+pub fn random_program(rng: &mut Rng) -> Vec<i64> {
+    let opcodes: Vec<Opcode> = (0..rng.range(4, 40))
+        .map(|_| Opcode::all()[rng.range(0, 9) as usize])
+        .chain(vec![Opcode::Hlt])
+        .collect();
+    let mut starts = Vec::new();
+    let mut end = 0;
+    for opcode in &opcodes {
+        starts.push(end as i64);
+        end += opcode.parameter_count() + 1;
+    }
+    let size = (end + DATA_SIZE) as i64;
+
+    let mut program = Vec::new();
+    for opcode in opcodes {
+        let parameters = (0..opcode.parameter_count())
+            .map(|i| {
+                if opcode.write_parameter() == Some(i) {
+                    write_operand(rng, size)
+                } else if opcode.is_jump() && i == 1 && rng.chance(80) {
+                    immediate(starts[rng.range(0, starts.len() as i64) as usize])
+                } else if opcode == Opcode::Arb {
+                    immediate(rng.range(-1, 6))
+                } else {
+                    read_operand(rng, size)
+                }
+            })
+            .collect();
+        program.extend(Instruction { opcode, parameters }.encode());
+    }
+    program.extend((0..DATA_SIZE).map(|_| rng.range(-20, 20)));
+    program
+}
+
+fn immediate(value: i64) -> Parameter {
+    Parameter {
+        mode: Mode::Immediate,
+        value,
+    }
+}
+
+fn read_operand(rng: &mut Rng, size: i64) -> Parameter {
+    if rng.chance(40) {
+        immediate(rng.range(-10, 10))
+    } else {
+        write_operand(rng, size)
+    }
+}
+
+fn write_operand(rng: &mut Rng, size: i64) -> Parameter {
+    let (mode, value) = match rng.range(0, 20) {
+        0..=9 => (Mode::Position, rng.range(size - DATA_SIZE as i64, size)),
+        10..=12 => (Mode::Position, rng.range(0, size)),
+        13 => (Mode::Position, FAR_ADDRESS),
+        _ => (Mode::Relative, rng.range(-1, 12)),
+    };
+    Parameter { mode, value }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum End {
+    Halted,
+    AwaitingInput,
+    Failed(IntcodeError),
+    // The next instruction would overflow an i64, which panics in debug builds
+    Overflow,
+    StepLimit,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    pub outputs: Vec<i64>,
+    pub end: End,
+    // Every non-zero cell once the run stopped
+    pub memory: Vec<(i64, i64)>,
+}
+
+// What the harness needs from a way of running a program
+pub trait Backend {
+    type Memory: MemoryBackend;
+    fn intcode(&self) -> &IntCode<Self::Memory>;
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError>;
+}
+
+impl<M: MemoryBackend> Backend for IntCode<M> {
+    type Memory = M;
+
+    fn intcode(&self) -> &IntCode<M> {
+        self
+    }
+
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        self.step_state()
+    }
+}
+
+impl<M: MemoryBackend + 'static> Backend for CompiledIntCode<M> {
+    type Memory = M;
+
+    fn intcode(&self) -> &IntCode<M> {
+        CompiledIntCode::intcode(self)
+    }
+
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        self.step_state()
+    }
+}
+
+// Runs until the program halts, fails, runs out of input or hits the step limit
+pub fn execute<B: Backend>(backend: &mut B) -> Outcome {
+    let mut outputs = Vec::new();
+    let mut end = End::StepLimit;
+    for _ in 0..STEP_LIMIT {
+        if overflows(backend.intcode()) {
+            end = End::Overflow;
+            break;
+        }
+        match backend.step() {
+            Ok(Some(RunState::Output(value))) => outputs.push(value),
+            Ok(Some(RunState::Halted)) => {
+                end = End::Halted;
+                break;
+            }
+            Ok(Some(RunState::AwaitingInput)) => {
+                end = End::AwaitingInput;
+                break;
+            }
+            Ok(None) => (),
+            Err(error) => {
+                end = End::Failed(error);
+                break;
+            }
+        }
+    }
+    Outcome {
+        outputs,
+        end,
+        memory: backend.intcode().memory.cells(),
+    }
+}
+
+fn overflows<M: MemoryBackend>(intcode: &IntCode<M>) -> bool {
+    let instruction = match intcode.instruction_at(intcode.program_counter()) {
+        Some(instruction) => instruction,
+        None => return false,
+    };
+    let read = |p: &Parameter| match p.mode {
+        Mode::Immediate => Some(p.value),
+        Mode::Position => intcode.memory.read(p.value).ok(),
+        Mode::Relative => intcode.memory.read(intcode.relative_base() + p.value).ok(),
+    };
+    let (a, b) = match instruction.opcode {
+        Opcode::Add | Opcode::Mul | Opcode::Arb => (
+            read(&instruction.parameters[0]),
+            match instruction.opcode {
+                Opcode::Arb => Some(intcode.relative_base()),
+                _ => read(&instruction.parameters[1]),
+            },
+        ),
+        _ => return false,
+    };
+    match (a, b, instruction.opcode) {
+        (Some(a), Some(b), Opcode::Mul) => a.checked_mul(b).is_none(),
+        (Some(a), Some(b), _) => a.checked_add(b).is_none(),
+        _ => false,
+    }
+}
+
+#[derive(Debug)]
+pub struct Mismatch {
+    pub program: Vec<i64>,
+    pub inputs: Vec<i64>,
+    // Name of each backend and what it did, the interpreter first
+    pub outcomes: Vec<(&'static str, Outcome)>,
+}
+
+// Runs `cases` random programs with random inputs through the interpreter and every
+// other backend, returning the first case where they disagree
+pub fn differential(seed: u64, cases: usize) -> Result<(), Box<Mismatch>> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..cases {
+        let program = random_program(&mut rng);
+        let inputs: Vec<i64> = (0..rng.range(0, 6)).map(|_| rng.range(-10, 10)).collect();
+        let outcomes = vec![
+            (
+                "interpreter",
+                execute(&mut loaded::<PagedMemory>(&program, &inputs)),
+            ),
+            (
+                "hash memory",
+                execute(&mut loaded::<HashMemory>(&program, &inputs)),
+            ),
+            (
+                "compiled",
+                execute(&mut CompiledIntCode::compile(loaded::<PagedMemory>(
+                    &program, &inputs,
+                ))),
+            ),
+        ];
+        if outcomes.iter().any(|(_, o)| *o != outcomes[0].1) {
+            return Err(Box::new(Mismatch {
+                program,
+                inputs,
+                outcomes,
+            }));
+        }
+    }
+    Ok(())
+}
+
+fn loaded<M: MemoryBackend>(program: &[i64], inputs: &[i64]) -> IntCode<M> {
+    let mut intcode = IntCode::with_backend(program);
+    for value in inputs {
+        intcode.input.push(*value);
+    }
+    intcode
+}
+
+#[test]
+fn test_random_programs_decode() {
+    let mut rng = Rng::new(7);
+    for _ in 0..50 {
+        let program = random_program(&mut rng);
+        let mut address = 0;
+        loop {
+            let instruction = Instruction::decode(&program[address..]).unwrap();
+            address += instruction.size();
+            if instruction.opcode == Opcode::Hlt {
+                break;
+            }
+        }
+        assert_eq!(program.len() - address, DATA_SIZE);
+    }
+}
+
+#[test]
+fn test_differential() {
+    if let Err(mismatch) = differential(2019, 500) {
+        panic!("backends disagree: {:#?}", mismatch);
+    }
+}
@@ -0,0 +1,233 @@
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    Add,
+    Mul,
+    In,
+    Out,
+    Jnz,
+    Jz,
+    Lt,
+    Eq,
+    Arb,
+    Hlt,
+}
+
+impl Opcode {
+    pub fn all() -> Vec<Opcode> {
+        vec![
+            Opcode::Add,
+            Opcode::Mul,
+            Opcode::In,
+            Opcode::Out,
+            Opcode::Jnz,
+            Opcode::Jz,
+            Opcode::Lt,
+            Opcode::Eq,
+            Opcode::Arb,
+            Opcode::Hlt,
+        ]
+    }
+
+    pub fn from_value(value: i64) -> Option<Opcode> {
+        match value {
+            1 => Some(Opcode::Add),
+            2 => Some(Opcode::Mul),
+            3 => Some(Opcode::In),
+            4 => Some(Opcode::Out),
+            5 => Some(Opcode::Jnz),
+            6 => Some(Opcode::Jz),
+            7 => Some(Opcode::Lt),
+            8 => Some(Opcode::Eq),
+            9 => Some(Opcode::Arb),
+            99 => Some(Opcode::Hlt),
+            _ => None,
+        }
+    }
+
+    pub fn value(self) -> i64 {
+        match self {
+            Opcode::Add => 1,
+            Opcode::Mul => 2,
+            Opcode::In => 3,
+            Opcode::Out => 4,
+            Opcode::Jnz => 5,
+            Opcode::Jz => 6,
+            Opcode::Lt => 7,
+            Opcode::Eq => 8,
+            Opcode::Arb => 9,
+            Opcode::Hlt => 99,
+        }
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Add => "ADD",
+            Opcode::Mul => "MUL",
+            Opcode::In => "IN",
+            Opcode::Out => "OUT",
+            Opcode::Jnz => "JNZ",
+            Opcode::Jz => "JZ",
+            Opcode::Lt => "LT",
+            Opcode::Eq => "EQ",
+            Opcode::Arb => "ARB",
+            Opcode::Hlt => "HLT",
+        }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+        Opcode::all()
+            .into_iter()
+            .find(|o| o.mnemonic().eq_ignore_ascii_case(mnemonic))
+    }
+
+    pub fn parameter_count(self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Mul | Opcode::Lt | Opcode::Eq => 3,
+            Opcode::Jnz | Opcode::Jz => 2,
+            Opcode::In | Opcode::Out | Opcode::Arb => 1,
+            Opcode::Hlt => 0,
+        }
+    }
+
+    // Index of the parameter the instruction writes to, if any
+    pub fn write_parameter(self) -> Option<usize> {
+        match self {
+            Opcode::Add | Opcode::Mul | Opcode::Lt | Opcode::Eq => Some(2),
+            Opcode::In => Some(0),
+            _ => None,
+        }
+    }
+
+    pub fn is_jump(self) -> bool {
+        self == Opcode::Jnz || self == Opcode::Jz
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl Mode {
+    pub fn from_value(value: i64) -> Option<Mode> {
+        match value {
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
+        }
+    }
+
+    pub fn value(self) -> i64 {
+        match self {
+            Mode::Position => 0,
+            Mode::Immediate => 1,
+            Mode::Relative => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Parameter {
+    pub mode: Mode,
+    pub value: i64,
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode {
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Immediate => write!(f, "#{}", self.value),
+            Mode::Relative => write!(f, "rb[{}]", self.value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub parameters: Vec<Parameter>,
+}
+
+impl Instruction {
+    // Decodes the instruction at the start of `words`. Returns None for anything the VM
+    // would not execute cleanly: unknown opcodes, unknown modes, immediate writes, mode
+    // digits for parameters that do not exist or a truncated instruction.
+    pub fn decode(words: &[i64]) -> Option<Instruction> {
+        let first = *words.first()?;
+        if first < 0 {
+            return None;
+        }
+        let opcode = Opcode::from_value(first % 100)?;
+        let count = opcode.parameter_count();
+        if words.len() <= count {
+            return None;
+        }
+
+        let mut modes = first / 100;
+        let mut parameters = Vec::with_capacity(count);
+        for i in 0..count {
+            let mode = Mode::from_value(modes % 10)?;
+            if mode == Mode::Immediate && opcode.write_parameter() == Some(i) {
+                return None;
+            }
+            parameters.push(Parameter {
+                mode,
+                value: words[i + 1],
+            });
+            modes /= 10;
+        }
+        if modes != 0 {
+            return None;
+        }
+
+        Some(Instruction { opcode, parameters })
+    }
+
+    pub fn size(&self) -> usize {
+        self.parameters.len() + 1
+    }
+
+    pub fn encode(&self) -> Vec<i64> {
+        let mut modes = 0;
+        for p in self.parameters.iter().rev() {
+            modes = modes * 10 + p.mode.value();
+        }
+        let mut words = vec![modes * 100 + self.opcode.value()];
+        words.extend(self.parameters.iter().map(|p| p.value));
+        words
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.opcode.mnemonic())?;
+        for (i, p) in self.parameters.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, p)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_decode() {
+    let instruction = Instruction::decode(&[1002, 4, 3, 4, 33]).unwrap();
+    assert_eq!(instruction.opcode, Opcode::Mul);
+    assert_eq!(instruction.size(), 4);
+    assert_eq!(instruction.to_string(), "MUL [4], #3, [4]");
+    assert_eq!(instruction.encode(), vec![1002, 4, 3, 4]);
+
+    let instruction = Instruction::decode(&[204, -1]).unwrap();
+    assert_eq!(instruction.to_string(), "OUT rb[-1]");
+
+    // Immediate write, unknown mode, stray mode digits and truncation
+    assert!(Instruction::decode(&[11101, 1, 2, 3]).is_none());
+    assert!(Instruction::decode(&[301, 1, 2, 3]).is_none());
+    assert!(Instruction::decode(&[10099]).is_none());
+    assert!(Instruction::decode(&[1, 2, 3]).is_none());
+    assert!(Instruction::decode(&[42]).is_none());
+}
@@ -0,0 +1,184 @@
+use crate::intcode::ErrorKind;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Cells below this address live in a Vec of pages, anything above in a sparse map
+const DENSE_LIMIT: i64 = 1 << 20;
+const PAGE_SIZE: usize = 1024;
+
+pub trait MemoryBackend: Clone {
+    fn from_program(program: &[i64]) -> Self;
+    fn read(&self, address: i64) -> Result<i64, ErrorKind>;
+    fn store(&mut self, address: i64, value: i64) -> Result<(), ErrorKind>;
+    // Every non-zero cell, ordered by address
+    fn cells(&self) -> Vec<(i64, i64)>;
+}
+
+// The original backend, one hash map entry per cell
+#[derive(Clone)]
+pub struct HashMemory {
+    mem: HashMap<i64, i64>,
+}
+
+impl MemoryBackend for HashMemory {
+    fn from_program(program: &[i64]) -> HashMemory {
+        let mut memory_map = HashMap::new();
+        for (i, value) in program.iter().enumerate() {
+            memory_map.insert(i as i64, *value);
+        }
+        HashMemory { mem: memory_map }
+    }
+
+    fn read(&self, address: i64) -> Result<i64, ErrorKind> {
+        if address < 0 {
+            return Err(ErrorKind::NegativeAddress(address));
+        }
+        Ok(*self.mem.get(&address).unwrap_or(&0i64))
+    }
+
+    fn store(&mut self, address: i64, value: i64) -> Result<(), ErrorKind> {
+        if address < 0 {
+            return Err(ErrorKind::NegativeAddress(address));
+        }
+        *self.mem.entry(address).or_insert(0) = value;
+        Ok(())
+    }
+
+    fn cells(&self) -> Vec<(i64, i64)> {
+        let mut cells: Vec<(i64, i64)> = self
+            .mem
+            .iter()
+            .filter(|(_, v)| **v != 0)
+            .map(|(k, v)| (*k, *v))
+            .collect();
+        cells.sort();
+        cells
+    }
+}
+
+// Pages are shared between clones and only copied when one of the copies writes to them
+type Page = [i64; PAGE_SIZE];
+
+#[derive(Clone)]
+pub struct PagedMemory {
+    dense: Vec<Arc<Page>>,
+    sparse: HashMap<i64, Arc<Page>>,
+}
+
+impl PagedMemory {
+    fn page_mut(&mut self, address: i64) -> &mut Page {
+        let index = address / PAGE_SIZE as i64;
+        if address < DENSE_LIMIT {
+            let index = index as usize;
+            if index >= self.dense.len() {
+                // Grow a page at a time, new pages share the same zeroed block
+                let zero = Arc::new([0; PAGE_SIZE]);
+                self.dense.resize(index + 1, zero);
+            }
+            Arc::make_mut(&mut self.dense[index])
+        } else {
+            Arc::make_mut(
+                self.sparse
+                    .entry(index)
+                    .or_insert_with(|| Arc::new([0; PAGE_SIZE])),
+            )
+        }
+    }
+}
+
+impl MemoryBackend for PagedMemory {
+    fn from_program(program: &[i64]) -> PagedMemory {
+        let dense = program
+            .chunks(PAGE_SIZE)
+            .map(|chunk| {
+                let mut page = [0; PAGE_SIZE];
+                page[..chunk.len()].copy_from_slice(chunk);
+                Arc::new(page)
+            })
+            .collect();
+        PagedMemory {
+            dense,
+            sparse: HashMap::new(),
+        }
+    }
+
+    fn read(&self, address: i64) -> Result<i64, ErrorKind> {
+        if address < 0 {
+            return Err(ErrorKind::NegativeAddress(address));
+        }
+        let index = address / PAGE_SIZE as i64;
+        let page = if address < DENSE_LIMIT {
+            self.dense.get(index as usize)
+        } else {
+            self.sparse.get(&index)
+        };
+        Ok(page.map(|p| p[address as usize % PAGE_SIZE]).unwrap_or(0))
+    }
+
+    fn store(&mut self, address: i64, value: i64) -> Result<(), ErrorKind> {
+        if address < 0 {
+            return Err(ErrorKind::NegativeAddress(address));
+        }
+        self.page_mut(address)[address as usize % PAGE_SIZE] = value;
+        Ok(())
+    }
+
+    fn cells(&self) -> Vec<(i64, i64)> {
+        let mut sparse: Vec<(&i64, &Arc<Page>)> = self.sparse.iter().collect();
+        sparse.sort_by_key(|(index, _)| **index);
+        let pages = self
+            .dense
+            .iter()
+            .enumerate()
+            .map(|(index, page)| (index as i64, page))
+            .chain(sparse.into_iter().map(|(index, page)| (*index, page)));
+
+        let mut cells = Vec::new();
+        for (index, page) in pages {
+            for (offset, value) in page.iter().enumerate() {
+                if *value != 0 {
+                    cells.push((index * PAGE_SIZE as i64 + offset as i64, *value));
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[test]
+fn test_paged_memory() {
+    let mut memory = PagedMemory::from_program(&[1, 2, 3]);
+    assert_eq!(memory.read(2), Ok(3));
+    assert_eq!(memory.read(3), Ok(0));
+    assert_eq!(memory.read(-1), Err(ErrorKind::NegativeAddress(-1)));
+
+    memory.store(5000, 7).unwrap();
+    assert_eq!(memory.dense.len(), 5);
+    assert_eq!(memory.read(5000), Ok(7));
+    assert_eq!(memory.read(4999), Ok(0));
+
+    memory.store(1 << 40, 9).unwrap();
+    assert_eq!(memory.sparse.len(), 1);
+    assert_eq!(memory.read(1 << 40), Ok(9));
+    assert_eq!(memory.read((1 << 40) + 1), Ok(0));
+    assert_eq!(memory.store(-5, 1), Err(ErrorKind::NegativeAddress(-5)));
+    assert_eq!(
+        memory.cells(),
+        vec![(0, 1), (1, 2), (2, 3), (5000, 7), (1 << 40, 9)]
+    );
+}
+
+#[test]
+fn test_paged_memory_copy_on_write() {
+    let mut original = PagedMemory::from_program(&[1, 2, 3]);
+    original.store(3000, 4).unwrap();
+    let mut copy = original.clone();
+    assert!(Arc::ptr_eq(&original.dense[0], &copy.dense[0]));
+
+    copy.store(1, 20).unwrap();
+    assert!(!Arc::ptr_eq(&original.dense[0], &copy.dense[0]));
+    assert!(Arc::ptr_eq(&original.dense[2], &copy.dense[2]));
+    assert_eq!(original.read(1), Ok(2));
+    assert_eq!(copy.read(1), Ok(20));
+    assert_eq!(copy.read(3000), Ok(4));
+}
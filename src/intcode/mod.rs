@@ -0,0 +1,683 @@
+pub mod ascii;
+pub mod asm;
+pub mod budget;
+pub mod cfg;
+pub mod compiled;
+pub mod debugger;
+pub mod disasm;
+pub mod fuzz;
+pub mod instruction;
+pub mod network;
+pub mod opcodes;
+pub mod phases;
+pub mod topology;
+
+mod channel;
+mod codemap;
+mod error;
+mod memory;
+mod state;
+mod trace;
+
+pub use channel::{AsciiInput, AsciiOutput, InputSource, IterInput, OutputSink, WithDefault};
+pub use codemap::SelfModification;
+pub use error::{ErrorKind, IntcodeError};
+pub use memory::{HashMemory, MemoryBackend, PagedMemory};
+pub use state::{StateError, STATE_VERSION};
+pub use trace::{Profiler, RingTracer, TraceEvent, Tracer, WriterTracer};
+
+use crate::intcode::budget::{Budget, CycleCounter};
+use crate::intcode::codemap::CodeMap;
+use crate::intcode::instruction::{Instruction, Mode, Opcode, Parameter};
+use crate::intcode::opcodes::{Effect, OpcodeTable, Operands};
+use crate::parse::{parse_separated, read_text, ParseError};
+use std::collections::BTreeSet;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+pub fn read_input<R: io::Read>(r: R) -> Result<Vec<i64>, ParseError> {
+    let program = parse_separated(&read_text(r)?, ',')?;
+    if program.is_empty() {
+        return Err(ParseError::new(1, 1, "empty program"));
+    }
+    Ok(program)
+}
+#[derive(Clone, Default)]
+pub struct IntInput {
+    data: Vec<i64>,
+    counter: usize,
+}
+
+impl IntInput {
+    pub fn new() -> IntInput {
+        IntInput::default()
+    }
+    pub fn has_input(&self) -> bool {
+        self.counter < self.data.len()
+    }
+    pub fn push(&mut self, value: i64) {
+        self.data.push(value);
+    }
+    // Values pushed but not read yet
+    pub fn pending(&self) -> &[i64] {
+        &self.data[self.counter..]
+    }
+    pub fn get(&mut self) -> i64 {
+        self.counter += 1;
+        self.data[self.counter - 1]
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunState {
+    Halted,
+    AwaitingInput,
+    Output(i64),
+}
+
+// Full machine state, restorable into any computer running the same backend
+#[derive(Clone)]
+pub struct Snapshot<M: MemoryBackend = PagedMemory>(IntCode<M>);
+
+#[derive(Clone)]
+pub struct IntCode<M: MemoryBackend = PagedMemory> {
+    pub memory: M,
+    pub input: IntInput,
+    program_counter: i64,
+    pub last_output: i64,
+    pub finished: bool,
+    relative_base: i64,
+    tracer: Option<Arc<Mutex<dyn Tracer>>>,
+    code_map: Option<CodeMap>,
+    opcodes: Option<Arc<OpcodeTable<M>>>,
+    counter: CycleCounter,
+    budget: Option<Budget>,
+}
+
+impl IntCode {
+    pub fn new(program: &Vec<i64>) -> IntCode {
+        IntCode::with_backend(program)
+    }
+}
+
+impl<M: MemoryBackend> IntCode<M> {
+    pub fn with_backend(program: &[i64]) -> IntCode<M> {
+        IntCode {
+            memory: M::from_program(program),
+            input: IntInput::new(),
+            program_counter: 0,
+            last_output: 0,
+            finished: false,
+            relative_base: 0,
+            tracer: None,
+            code_map: None,
+            opcodes: None,
+            counter: CycleCounter::default(),
+            budget: None,
+        }
+    }
+
+    pub fn program_counter(&self) -> i64 {
+        self.program_counter
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    // Decodes the instruction at `address` from live memory
+    pub fn instruction_at(&self, address: i64) -> Option<Instruction> {
+        let words: Result<Vec<i64>, ErrorKind> = (address..address + 4)
+            .map(|a| self.memory.read(a))
+            .collect();
+        Instruction::decode(&words.ok()?)
+    }
+
+    // Every executed instruction is reported to the tracer, clones share it
+    pub fn set_tracer<T: Tracer + 'static>(&mut self, tracer: Arc<Mutex<T>>) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn clear_tracer(&mut self) {
+        self.tracer = None;
+    }
+
+    // Instructions executed so far, waiting for input doesn't count
+    pub fn cycles(&self) -> u64 {
+        self.counter.cycles
+    }
+
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = Some(budget);
+    }
+
+    pub fn clear_budget(&mut self) {
+        self.budget = None;
+    }
+
+    pub(crate) fn check_budget(&self) -> Result<(), IntcodeError> {
+        match &self.budget {
+            Some(budget) => budget
+                .check(self.counter.cycles)
+                .map_err(|kind| IntcodeError {
+                    program_counter: self.program_counter,
+                    instruction: self.memory.read(self.program_counter).unwrap_or(0),
+                    kind,
+                }),
+            None => Ok(()),
+        }
+    }
+
+    // Opcodes outside the standard set are looked up in `opcodes`, clones share it
+    pub fn set_opcodes(&mut self, opcodes: Arc<OpcodeTable<M>>) {
+        self.opcodes = Some(opcodes);
+    }
+
+    // From now on, writes into cells that already ran as an instruction are recorded
+    pub fn track_self_modification(&mut self) {
+        self.code_map = Some(CodeMap::default());
+    }
+
+    pub fn self_modifications(&self) -> &[SelfModification] {
+        self.code_map.as_ref().map_or(&[], |m| m.events())
+    }
+
+    pub fn take_self_modifications(&mut self) -> Vec<SelfModification> {
+        self.code_map
+            .as_mut()
+            .map_or_else(Vec::new, |m| m.take_events())
+    }
+
+    // Each instruction that patched code, along with the instruction it patched
+    pub fn self_modification_sites(&self) -> BTreeSet<(i64, i64)> {
+        self.code_map
+            .as_ref()
+            .map_or_else(BTreeSet::new, |m| m.sites())
+    }
+
+    pub fn snapshot(&self) -> Snapshot<M> {
+        Snapshot(self.clone())
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot<M>) {
+        *self = snapshot.0.clone();
+    }
+
+    fn get_specific_parameter(offset: i64, parameters: i64) -> i64 {
+        let mut params = parameters;
+        for _ in 1..offset {
+            params = params / 10
+        }
+        params % 10
+    }
+
+    fn get_value(&self, offset: i64, parameters: i64) -> Result<i64, ErrorKind> {
+        let parameter = Self::get_specific_parameter(offset, parameters);
+
+        self.memory.read(match parameter {
+            // position
+            0 => self.memory.read(self.program_counter + offset)?,
+            // direct
+            1 => self.program_counter + offset,
+            // relative
+            2 => self.memory.read(self.program_counter + offset)? + self.relative_base,
+            p => return Err(ErrorKind::InvalidMode(p)),
+        })
+    }
+
+    fn get_store_index(&self, offset: i64, parameters: i64) -> Result<i64, ErrorKind> {
+        let parameter = Self::get_specific_parameter(offset, parameters);
+        match parameter {
+            // position
+            0 => self.memory.read(self.program_counter + offset),
+            // relative
+            2 => Ok(self.memory.read(self.program_counter + offset)? + self.relative_base),
+            1 => Err(ErrorKind::ImmediateWrite),
+            p => Err(ErrorKind::InvalidMode(p)),
+        }
+    }
+
+    pub fn step<O: OutputSink + ?Sized>(&mut self, output: &mut O) -> Result<bool, IntcodeError> {
+        match self.step_state()? {
+            None => Ok(true),
+            Some(RunState::Output(value)) => {
+                output.send(value);
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+        }
+    }
+
+    // Executes a single instruction, returning the state change it caused if any
+    pub fn step_state(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let mut input = std::mem::take(&mut self.input);
+        let state = self.step_from(&mut input);
+        self.input = input;
+        state
+    }
+
+    // Same as `step_state`, reading from `input` instead of the computer's own queue
+    pub fn step_from<I: InputSource + ?Sized>(
+        &mut self,
+        input: &mut I,
+    ) -> Result<Option<RunState>, IntcodeError> {
+        self.check_budget()?;
+        let program_counter = self.program_counter;
+        let traced = self.tracer.as_ref().map(|_| self.trace_event());
+        let tracked = self
+            .code_map
+            .as_ref()
+            .map(|_| (self.instruction_size(), self.write_address()));
+        let state = self.execute(input).map_err(|kind| IntcodeError {
+            program_counter,
+            instruction: self.memory.read(program_counter).unwrap_or(0),
+            kind,
+        })?;
+        if state != Some(RunState::AwaitingInput) {
+            self.counter.tick();
+        }
+
+        if let (Some(tracer), Some((mut event, write_address))) = (&self.tracer, traced) {
+            if state != Some(RunState::AwaitingInput) {
+                event.write = write_address.map(|a| (a, self.memory.read(a).unwrap_or(0)));
+                tracer.lock().unwrap().trace(&event);
+            }
+        }
+        if let (Some(code_map), Some((size, write))) = (&mut self.code_map, tracked) {
+            if state != Some(RunState::AwaitingInput) {
+                code_map.executed(program_counter, size);
+                if let Some(address) = write {
+                    code_map.written(
+                        program_counter,
+                        address,
+                        self.memory.read(address).unwrap_or(0),
+                    );
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    // Parameters of the instruction about to run and which one it writes through, for
+    // standard and custom opcodes alike
+    fn decode_current(&self) -> Option<(Vec<Parameter>, Option<usize>)> {
+        if let Some(instruction) = self.instruction_at(self.program_counter) {
+            return Some((instruction.parameters, instruction.opcode.write_parameter()));
+        }
+        let opcode = self.memory.read(self.program_counter).ok()? % 100;
+        let custom = self.opcodes.as_ref()?.get(opcode)?;
+        let parameters = opcodes::parameters(self, self.program_counter, custom)?;
+        Some((parameters, custom.write_parameter))
+    }
+
+    fn instruction_size(&self) -> i64 {
+        let opcode = self.memory.read(self.program_counter).unwrap_or(0) % 100;
+        match Opcode::from_value(opcode) {
+            Some(opcode) => opcode.parameter_count() as i64 + 1,
+            None => self
+                .opcodes
+                .as_ref()
+                .and_then(|t| t.get(opcode))
+                .map_or(1, |c| c.parameter_count as i64 + 1),
+        }
+    }
+
+    // The address the instruction about to run will write to, if any
+    fn write_address(&self) -> Option<i64> {
+        let instruction = self.memory.read(self.program_counter).ok()?;
+        let opcode = instruction % 100;
+        let parameter = match Opcode::from_value(opcode) {
+            Some(opcode) => opcode.write_parameter()?,
+            None => self.opcodes.as_ref()?.get(opcode)?.write_parameter?,
+        };
+        self.get_store_index(parameter as i64 + 1, instruction / 100)
+            .ok()
+    }
+
+    // Resolves the operands of the instruction about to run, along with the address it
+    // will write to
+    fn trace_event(&self) -> (TraceEvent, Option<i64>) {
+        let mut operands = Vec::new();
+        let mut write_address = None;
+        if let Some((parameters, write_parameter)) = self.decode_current() {
+            for (i, parameter) in parameters.iter().enumerate() {
+                let address = match parameter.mode {
+                    Mode::Position => parameter.value,
+                    Mode::Relative => self.relative_base + parameter.value,
+                    Mode::Immediate => {
+                        operands.push(parameter.value);
+                        continue;
+                    }
+                };
+                if write_parameter == Some(i) {
+                    write_address = Some(address);
+                    operands.push(address);
+                } else {
+                    operands.push(self.memory.read(address).unwrap_or(0));
+                }
+            }
+        }
+        let event = TraceEvent {
+            program_counter: self.program_counter,
+            relative_base: self.relative_base,
+            instruction: self.memory.read(self.program_counter).unwrap_or(0),
+            operands,
+            write: None,
+        };
+        (event, write_address)
+    }
+
+    fn execute<I: InputSource + ?Sized>(
+        &mut self,
+        input: &mut I,
+    ) -> Result<Option<RunState>, ErrorKind> {
+        if self.finished {
+            return Err(ErrorKind::Halted);
+        }
+        let instruction = self.memory.read(self.program_counter)?;
+        let opcode = instruction % 100;
+        let parameters = instruction / 100;
+        match opcode {
+            1 => {
+                let store_idx = self.get_store_index(3, parameters)?;
+                let value1 = self.get_value(1, parameters)?;
+                let value2 = self.get_value(2, parameters)?;
+                self.memory.store(store_idx, value1 + value2)?;
+                self.program_counter += 4;
+            }
+            2 => {
+                let store_idx = self.get_store_index(3, parameters)?;
+                let value1 = self.get_value(1, parameters)?;
+                let value2 = self.get_value(2, parameters)?;
+                self.memory.store(store_idx, value1 * value2)?;
+                self.program_counter += 4;
+            }
+            3 => {
+                let store_idx = self.get_store_index(1, parameters)?;
+                let value = match input.next_input() {
+                    Some(value) => value,
+                    None => return Ok(Some(RunState::AwaitingInput)),
+                };
+                self.memory.store(store_idx, value)?;
+                self.program_counter += 2;
+            }
+            4 => {
+                let output_value = self.get_value(1, parameters)?;
+                self.last_output = output_value;
+                self.program_counter += 2;
+                return Ok(Some(RunState::Output(output_value)));
+            }
+            5 => {
+                let test_value = self.get_value(1, parameters)?;
+                let jump_location = self.get_value(2, parameters)?;
+                if test_value == 0 {
+                    self.program_counter += 3;
+                } else {
+                    self.program_counter = jump_location;
+                }
+            }
+            6 => {
+                let test_value = self.get_value(1, parameters)?;
+                let jump_location = self.get_value(2, parameters)?;
+                if test_value == 0 {
+                    self.program_counter = jump_location;
+                } else {
+                    self.program_counter += 3;
+                }
+            }
+            7 => {
+                let store_idx = self.get_store_index(3, parameters)?;
+                let value1 = self.get_value(1, parameters)?;
+                let value2 = self.get_value(2, parameters)?;
+                self.memory
+                    .store(store_idx, if value1 < value2 { 1 } else { 0 })?;
+                self.program_counter += 4;
+            }
+            8 => {
+                let store_idx = self.get_store_index(3, parameters)?;
+                let value1 = self.get_value(1, parameters)?;
+                let value2 = self.get_value(2, parameters)?;
+                self.memory
+                    .store(store_idx, if value1 == value2 { 1 } else { 0 })?;
+                self.program_counter += 4;
+            }
+            9 => {
+                let value = self.get_value(1, parameters)?;
+                self.relative_base += value;
+                self.program_counter += 2;
+            }
+            99 => {
+                self.finished = true;
+                return Ok(Some(RunState::Halted));
+            }
+            _ => return self.execute_custom(opcode, parameters),
+        }
+        Ok(None)
+    }
+
+    fn execute_custom(
+        &mut self,
+        opcode: i64,
+        parameters: i64,
+    ) -> Result<Option<RunState>, ErrorKind> {
+        let custom = match self.opcodes.as_ref().and_then(|t| t.get(opcode)) {
+            Some(custom) => custom.clone(),
+            None => return Err(ErrorKind::InvalidOpcode(opcode)),
+        };
+        let effect = custom.run(&mut Operands {
+            intcode: self,
+            modes: parameters,
+            write_parameter: custom.write_parameter,
+        })?;
+        let next = self.program_counter + custom.parameter_count as i64 + 1;
+        match effect {
+            Effect::Continue => self.program_counter = next,
+            Effect::Jump(target) => self.program_counter = target,
+            Effect::Output(value) => {
+                self.last_output = value;
+                self.program_counter = next;
+                return Ok(Some(RunState::Output(value)));
+            }
+            Effect::Halt => {
+                self.finished = true;
+                return Ok(Some(RunState::Halted));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn advance<O: OutputSink + ?Sized>(&mut self, output: &mut O) -> Result<(), IntcodeError> {
+        while self.step(output)? {}
+        Ok(())
+    }
+
+    // Runs until the program halts or `input` has nothing left, passing every output on
+    pub fn run_with<I: InputSource + ?Sized, O: OutputSink + ?Sized>(
+        &mut self,
+        input: &mut I,
+        output: &mut O,
+    ) -> Result<RunState, IntcodeError> {
+        loop {
+            match self.step_from(input)? {
+                None => (),
+                Some(RunState::Output(value)) => output.send(value),
+                Some(state) => return Ok(state),
+            }
+        }
+    }
+
+    // Runs until the next output, until an input is needed or until the program halts
+    pub fn run(&mut self) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step_state()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    // Collects up to `count` outputs, stopping early if the program halts or needs input
+    pub fn run_for_outputs(&mut self, count: usize) -> Result<Vec<i64>, IntcodeError> {
+        let mut outputs = Vec::with_capacity(count);
+        while outputs.len() < count {
+            match self.run()? {
+                RunState::Output(value) => outputs.push(value),
+                _ => break,
+            }
+        }
+        Ok(outputs)
+    }
+
+    // Collects every output until the program halts or needs input
+    pub fn run_until_blocked(&mut self) -> Result<(Vec<i64>, RunState), IntcodeError> {
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                RunState::Output(value) => outputs.push(value),
+                state => return Ok((outputs, state)),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_relative() {
+    let mut code_output = IntInput::new();
+    let mut code = IntCode::new(&vec![104, 1125899906842624, 99]);
+    while !code.finished {
+        code.advance(&mut code_output).unwrap();
+    }
+    assert!(code.finished);
+    assert!(code.last_output == 1125899906842624);
+
+    let mut code_output = IntInput::new();
+    let mut code = IntCode::new(&vec![1102, 34915192, 34915192, 7, 4, 7, 99, 0]);
+    while !code.finished {
+        code.advance(&mut code_output).unwrap();
+    }
+    assert!(code.finished);
+    assert!(code.last_output == 1219070632396864);
+
+    let mut code_output = IntInput::new();
+    let mut code = IntCode::new(&vec![
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ]);
+    while !code.finished {
+        code.advance(&mut code_output).unwrap();
+    }
+    assert!(code.finished);
+    assert!(code_output.data.len() == 16);
+}
+
+#[test]
+fn test_errors() {
+    let mut output = IntInput::new();
+
+    let mut code = IntCode::new(&vec![1101, 1, 1, 5, 42, 0]);
+    let error = code.advance(&mut output).unwrap_err();
+    assert_eq!(error.program_counter, 4);
+    assert_eq!(error.instruction, 42);
+    assert_eq!(error.kind, ErrorKind::InvalidOpcode(42));
+
+    let mut code = IntCode::new(&vec![10001, 0, 0, 0, 99]);
+    assert_eq!(
+        code.advance(&mut output).unwrap_err().kind,
+        ErrorKind::ImmediateWrite
+    );
+
+    let mut code = IntCode::new(&vec![301, 0, 0, 0, 99]);
+    assert_eq!(
+        code.advance(&mut output).unwrap_err().kind,
+        ErrorKind::InvalidMode(3)
+    );
+
+    let mut code = IntCode::new(&vec![4, -3, 99]);
+    assert_eq!(
+        code.advance(&mut output).unwrap_err().kind,
+        ErrorKind::NegativeAddress(-3)
+    );
+
+    let mut code = IntCode::new(&vec![99]);
+    code.advance(&mut output).unwrap();
+    assert!(code.finished);
+    let error = code.step(&mut output).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Halted);
+    assert_eq!(
+        error.to_string(),
+        "computer called after having finished at pc 0 (instruction 99)"
+    );
+}
+
+#[test]
+fn test_run_states() {
+    // Echoes two inputs, then halts
+    let mut code = IntCode::new(&vec![3, 9, 4, 9, 3, 9, 4, 9, 99, 0]);
+    assert_eq!(code.run(), Ok(RunState::AwaitingInput));
+    code.input.push(7);
+    assert_eq!(code.run(), Ok(RunState::Output(7)));
+    assert_eq!(code.run(), Ok(RunState::AwaitingInput));
+    code.input.push(8);
+    assert_eq!(code.run_until_blocked(), Ok((vec![8], RunState::Halted)));
+    assert!(code.finished);
+
+    let mut code = IntCode::new(&vec![
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ]);
+    assert_eq!(code.run_for_outputs(3), Ok(vec![109, 1, 204]));
+    assert_eq!(code.run_for_outputs(100).unwrap().len(), 13);
+    assert!(code.finished);
+}
+
+#[test]
+fn test_backends_agree() {
+    let program = vec![
+        3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+        1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+        1105, 1, 46, 98, 99,
+    ];
+    for input in 6..11 {
+        let mut paged = IntCode::new(&program);
+        let mut hashed: IntCode<HashMemory> = IntCode::with_backend(&program);
+        paged.input.push(input);
+        hashed.input.push(input);
+        assert_eq!(paged.run_until_blocked(), hashed.run_until_blocked());
+        for address in 0..program.len() as i64 {
+            assert_eq!(paged.memory.read(address), hashed.memory.read(address));
+        }
+    }
+}
+
+#[test]
+fn test_snapshot() {
+    // Adds up every input, printing the running total
+    let program = vec![3, 11, 1, 11, 12, 12, 4, 12, 1105, 1, 0, 0, 0];
+    let mut code = IntCode::new(&program);
+    code.input.push(5);
+    code.input.push(10);
+    assert_eq!(code.run_for_outputs(1), Ok(vec![5]));
+
+    let snapshot = code.snapshot();
+    assert_eq!(
+        code.run_until_blocked(),
+        Ok((vec![15], RunState::AwaitingInput))
+    );
+
+    let mut branch = code.clone();
+    branch.input.push(1);
+    assert_eq!(branch.run_for_outputs(1), Ok(vec![16]));
+
+    code.restore(&snapshot);
+    code.input.push(100);
+    assert_eq!(code.run_for_outputs(2), Ok(vec![15, 115]));
+}
+
+#[test]
+fn test_read_input() {
+    assert_eq!(
+        read_input("1,0,0,3,99\n".as_bytes()),
+        Ok(vec![1, 0, 0, 3, 99])
+    );
+    assert_eq!(
+        read_input(" \n\n".as_bytes()),
+        Err(ParseError::new(1, 1, "empty program"))
+    );
+    assert!(read_input("".as_bytes()).is_err());
+}
@@ -0,0 +1,411 @@
+use crate::intcode::{IntCode, IntcodeError, MemoryBackend, PagedMemory, RunState};
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+// Where packets for addresses outside the network end up by convention
+pub const NAT_ADDRESS: i64 = 255;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub destination: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+pub trait Nat {
+    // Receives every packet sent to an address with no machine behind it
+    fn receive(&mut self, packet: Packet) -> Control;
+    // Called when every queue is empty and every machine is polling. The packet returned
+    // is routed like any other, returning None stops the network as nothing else can happen
+    fn wake(&mut self) -> Option<Packet>;
+}
+
+// Sees every routed packet, `source` is NAT_ADDRESS for packets sent by the NAT
+pub trait Monitor {
+    fn observe(&mut self, source: i64, packet: &Packet);
+}
+
+impl<F: FnMut(i64, &Packet)> Monitor for F {
+    fn observe(&mut self, source: i64, packet: &Packet) {
+        self(source, packet)
+    }
+}
+
+pub struct Network<M: MemoryBackend = PagedMemory> {
+    machines: Vec<IntCode<M>>,
+    queues: Vec<VecDeque<i64>>,
+    monitor: Option<Box<dyn Monitor>>,
+}
+
+impl Network {
+    pub fn new(program: &[i64], size: usize) -> Network {
+        Network::with_backend(program, size)
+    }
+}
+
+impl<M: MemoryBackend> Network<M> {
+    // Every machine runs `program` and is told its address first
+    pub fn with_backend(program: &[i64], size: usize) -> Network<M> {
+        Network {
+            machines: vec![IntCode::with_backend(program); size],
+            queues: (0..size)
+                .map(|i| vec![i as i64].into_iter().collect())
+                .collect(),
+            monitor: None,
+        }
+    }
+
+    pub fn set_monitor<T: Monitor + 'static>(&mut self, monitor: T) {
+        self.monitor = Some(Box::new(monitor));
+    }
+
+    pub fn machine(&self, address: usize) -> &IntCode<M> {
+        &self.machines[address]
+    }
+
+    // Runs every machine in turn on the calling thread until the NAT stops the network
+    pub fn run<N: Nat>(&mut self, nat: &mut N) -> Result<(), IntcodeError> {
+        let size = self.machines.len();
+        let mut partial = vec![Vec::new(); size];
+        loop {
+            let mut idle = true;
+            for (i, outputs) in partial.iter_mut().enumerate() {
+                idle &= poll(&mut self.machines[i], &mut self.queues[i], outputs)?;
+                for packet in complete_packets(outputs) {
+                    let queues = &mut self.queues;
+                    let control = route(&mut self.monitor, nat, size, i as i64, packet, |j, p| {
+                        queues[j].push_back(p.x);
+                        queues[j].push_back(p.y);
+                    });
+                    if control == Control::Stop {
+                        return Ok(());
+                    }
+                }
+            }
+
+            while idle && self.queues.iter().all(|q| q.is_empty()) {
+                let packet = match nat.wake() {
+                    Some(packet) => packet,
+                    None => return Ok(()),
+                };
+                let queues = &mut self.queues;
+                let control = route(&mut self.monitor, nat, size, NAT_ADDRESS, packet, |j, p| {
+                    queues[j].push_back(p.x);
+                    queues[j].push_back(p.y);
+                });
+                if control == Control::Stop {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // Runs every machine on its own thread, routing packets on the calling one. The order
+    // packets reach the NAT depends on scheduling, but the network only counts as idle once
+    // every machine is waiting on an empty queue and no packet is left to route. Values a
+    // machine had taken but not read when the NAT stops the network are dropped
+    pub fn run_threaded<N: Nat>(&mut self, nat: &mut N) -> Result<(), IntcodeError>
+    where
+        M: Send,
+    {
+        let size = self.machines.len();
+        let shared = Shared {
+            state: Mutex::new(Switchboard {
+                queues: std::mem::take(&mut self.queues),
+                outbox: VecDeque::new(),
+                polling: 0,
+                stopped: false,
+                error: None,
+            }),
+            changed: Condvar::new(),
+        };
+
+        let result = thread::scope(|scope| {
+            for (address, intcode) in self.machines.iter_mut().enumerate() {
+                let shared = &shared;
+                scope.spawn(move || machine_thread(address, intcode, shared));
+            }
+
+            let mut state = shared.state.lock().unwrap();
+            let result = loop {
+                if let Some(error) = state.error.take() {
+                    break Err(error);
+                }
+                let (source, packet) = match state.outbox.pop_front() {
+                    Some((source, packet)) => (source as i64, packet),
+                    None if state.polling == size && state.queues.iter().all(|q| q.is_empty()) => {
+                        match nat.wake() {
+                            Some(packet) => (NAT_ADDRESS, packet),
+                            None => break Ok(()),
+                        }
+                    }
+                    None => {
+                        state = shared.changed.wait(state).unwrap();
+                        continue;
+                    }
+                };
+                let queues = &mut state.queues;
+                let control = route(&mut self.monitor, nat, size, source, packet, |j, p| {
+                    queues[j].push_back(p.x);
+                    queues[j].push_back(p.y);
+                });
+                shared.changed.notify_all();
+                if control == Control::Stop {
+                    break Ok(());
+                }
+            };
+            state.stopped = true;
+            shared.changed.notify_all();
+            result
+        });
+
+        self.queues = shared.state.into_inner().unwrap().queues;
+        result
+    }
+}
+
+// Everything the machine threads and the router share, behind one lock so idle checks see
+// a consistent picture
+struct Switchboard {
+    queues: Vec<VecDeque<i64>>,
+    // Packets sent by machines that the router hasn't handled yet
+    outbox: VecDeque<(usize, Packet)>,
+    // Machines waiting for their queue to fill after an idle poll
+    polling: usize,
+    stopped: bool,
+    error: Option<IntcodeError>,
+}
+
+struct Shared {
+    state: Mutex<Switchboard>,
+    changed: Condvar,
+}
+
+fn machine_thread<M: MemoryBackend>(address: usize, intcode: &mut IntCode<M>, shared: &Shared) {
+    let mut queue: VecDeque<i64> = shared.state.lock().unwrap().queues[address]
+        .drain(..)
+        .collect();
+    let mut partial = Vec::new();
+    loop {
+        let polled = poll(intcode, &mut queue, &mut partial);
+        let mut state = shared.state.lock().unwrap();
+        let idle = match polled {
+            Ok(idle) => idle,
+            Err(error) => {
+                state.error = Some(error);
+                shared.changed.notify_all();
+                return;
+            }
+        };
+        for packet in complete_packets(&mut partial) {
+            state.outbox.push_back((address, packet));
+        }
+
+        // Polling an empty queue does nothing, so sleep until something arrives
+        if idle && state.queues[address].is_empty() {
+            state.polling += 1;
+            shared.changed.notify_all();
+            while state.queues[address].is_empty() && !state.stopped {
+                state = shared.changed.wait(state).unwrap();
+            }
+            state.polling -= 1;
+        } else {
+            shared.changed.notify_all();
+        }
+        if state.stopped {
+            return;
+        }
+        queue.extend(state.queues[address].drain(..));
+    }
+}
+
+// Runs a machine until it polls an empty queue, then hands it a -1 and runs it to the next
+// poll. Returns whether it stayed idle: it had no packets waiting and sent nothing
+fn poll<M: MemoryBackend>(
+    intcode: &mut IntCode<M>,
+    queue: &mut VecDeque<i64>,
+    outputs: &mut Vec<i64>,
+) -> Result<bool, IntcodeError> {
+    if intcode.finished {
+        queue.clear();
+        return Ok(true);
+    }
+    let idle = queue.is_empty();
+    let sent = outputs.len();
+    if intcode.run_with(queue, outputs)? == RunState::Halted {
+        return Ok(idle && outputs.len() == sent);
+    }
+    queue.push_back(-1);
+    intcode.run_with(queue, outputs)?;
+    Ok(idle && outputs.len() == sent)
+}
+
+fn complete_packets(outputs: &mut Vec<i64>) -> Vec<Packet> {
+    let complete = outputs.len() - outputs.len() % 3;
+    outputs
+        .drain(..complete)
+        .collect::<Vec<i64>>()
+        .chunks(3)
+        .map(|c| Packet {
+            destination: c[0],
+            x: c[1],
+            y: c[2],
+        })
+        .collect()
+}
+
+// Hands a packet to the machine it's addressed to, or to the NAT if there's none
+fn route<N: Nat, D: FnMut(usize, &Packet)>(
+    monitor: &mut Option<Box<dyn Monitor>>,
+    nat: &mut N,
+    size: usize,
+    source: i64,
+    packet: Packet,
+    mut deliver: D,
+) -> Control {
+    if let Some(monitor) = monitor {
+        monitor.observe(source, &packet);
+    }
+    match packet.destination {
+        d if d >= 0 && (d as usize) < size => {
+            deliver(d as usize, &packet);
+            Control::Continue
+        }
+        _ => nat.receive(packet),
+    }
+}
+
+#[cfg(test)]
+struct Relay {
+    received: Vec<Packet>,
+    wakes: usize,
+}
+
+#[cfg(test)]
+impl Nat for Relay {
+    fn receive(&mut self, packet: Packet) -> Control {
+        self.received.push(packet);
+        Control::Continue
+    }
+
+    fn wake(&mut self) -> Option<Packet> {
+        if self.wakes == 3 {
+            return None;
+        }
+        self.wakes += 1;
+        let y = self.received.last().map(|p| p.y).unwrap_or(0);
+        Some(Packet {
+            destination: 0,
+            x: 7,
+            y,
+        })
+    }
+}
+
+#[test]
+fn test_network() {
+    use crate::intcode::asm::assemble;
+    use std::sync::{Arc, Mutex};
+
+    // Passes every packet on to the next address with y + 1, the last machine sends to the NAT.
+    // Each machine spins for a while first, so the others sit idle while it works
+    let program = assemble(
+        "
+                IN [addr]
+                ADD [addr], #1, [next]
+                EQ [next], #4, [t]
+                JZ [t], #poll
+                ADD #255, #0, [next]
+        poll:   IN [x]
+                EQ [x], #-1, [t]
+                JNZ [t], #poll
+                IN [y]
+                ADD #500, #0, [t]
+        spin:   ADD [t], #-1, [t]
+                JNZ [t], #spin
+                OUT [next]
+                OUT [x]
+                ADD [y], #1, [y]
+                OUT [y]
+                JZ #0, #poll
+        addr:   DATA 0
+        next:   DATA 0
+        t:      DATA 0
+        x:      DATA 0
+        y:      DATA 0
+        ",
+    )
+    .unwrap();
+
+    for threaded in &[false, true] {
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let log = observed.clone();
+        let mut network = Network::new(&program, 4);
+        network.set_monitor(move |source, packet: &Packet| {
+            log.lock().unwrap().push((source, packet.destination))
+        });
+
+        let mut nat = Relay {
+            received: Vec::new(),
+            wakes: 0,
+        };
+        if *threaded {
+            network.run_threaded(&mut nat).unwrap();
+        } else {
+            network.run(&mut nat).unwrap();
+        }
+
+        let ys: Vec<i64> = nat.received.iter().map(|p| p.y).collect();
+        assert_eq!(ys, vec![4, 8, 12]);
+        assert!(nat
+            .received
+            .iter()
+            .all(|p| p.destination == 255 && p.x == 7));
+        let observed = observed.lock().unwrap();
+        assert_eq!(observed.len(), 15);
+        assert_eq!(observed[..5], [(255, 0), (0, 1), (1, 2), (2, 3), (3, 255)]);
+    }
+}
+
+#[test]
+fn test_network_stop() {
+    struct First(Option<Packet>);
+
+    impl Nat for First {
+        fn receive(&mut self, packet: Packet) -> Control {
+            self.0 = Some(packet);
+            Control::Stop
+        }
+
+        fn wake(&mut self) -> Option<Packet> {
+            None
+        }
+    }
+
+    // Sends a single packet to address 300 straight away, then polls forever
+    let program = vec![3, 100, 104, 300, 104, 1, 104, 2, 3, 100, 1105, 1, 8];
+    let mut nat = First(None);
+    Network::new(&program, 3).run_threaded(&mut nat).unwrap();
+    assert_eq!(
+        nat.0,
+        Some(Packet {
+            destination: 300,
+            x: 1,
+            y: 2
+        })
+    );
+
+    let mut nat = First(None);
+    let mut network = Network::new(&[3, 100, 42], 2);
+    assert!(network.run(&mut nat).is_err());
+    assert!(Network::new(&[3, 100, 42], 2)
+        .run_threaded(&mut nat)
+        .is_err());
+}
@@ -0,0 +1,252 @@
+use crate::intcode::instruction::{Mode, Opcode, Parameter};
+use crate::intcode::{ErrorKind, IntCode, MemoryBackend, PagedMemory};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+// What a custom instruction does once it has run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    // Carry on with the next instruction
+    Continue,
+    Jump(i64),
+    Output(i64),
+    Halt,
+}
+
+type Handler<M> = dyn Fn(&mut Operands<M>) -> Result<Effect, ErrorKind> + Send + Sync;
+
+pub struct CustomOpcode<M: MemoryBackend> {
+    pub parameter_count: usize,
+    // Like `Opcode::write_parameter`, the only parameter `Operands::store` writes through
+    pub write_parameter: Option<usize>,
+    handler: Box<Handler<M>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegisterError {
+    // Outside 1..100, or one of the standard opcodes
+    Reserved(i64),
+    WriteParameter {
+        write_parameter: usize,
+        parameter_count: usize,
+    },
+}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterError::Reserved(opcode) => {
+                write!(f, "opcode {} is standard or out of range", opcode)
+            }
+            RegisterError::WriteParameter {
+                write_parameter,
+                parameter_count,
+            } => write!(
+                f,
+                "write parameter {} of an opcode with {} parameters",
+                write_parameter, parameter_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegisterError {}
+
+// Opcodes the VM runs on top of the standard ones, which can't be replaced
+pub struct OpcodeTable<M: MemoryBackend = PagedMemory> {
+    opcodes: HashMap<i64, Arc<CustomOpcode<M>>>,
+}
+
+impl<M: MemoryBackend> Default for OpcodeTable<M> {
+    fn default() -> OpcodeTable<M> {
+        OpcodeTable {
+            opcodes: HashMap::new(),
+        }
+    }
+}
+
+impl<M: MemoryBackend> OpcodeTable<M> {
+    pub fn new() -> OpcodeTable<M> {
+        OpcodeTable::default()
+    }
+
+    pub fn register<F>(
+        &mut self,
+        opcode: i64,
+        parameter_count: usize,
+        write_parameter: Option<usize>,
+        handler: F,
+    ) -> Result<(), RegisterError>
+    where
+        F: Fn(&mut Operands<M>) -> Result<Effect, ErrorKind> + Send + Sync + 'static,
+    {
+        if !(1..100).contains(&opcode) || Opcode::from_value(opcode).is_some() {
+            return Err(RegisterError::Reserved(opcode));
+        }
+        if let Some(w) = write_parameter.filter(|w| *w >= parameter_count) {
+            return Err(RegisterError::WriteParameter {
+                write_parameter: w,
+                parameter_count,
+            });
+        }
+        let custom = CustomOpcode {
+            parameter_count,
+            write_parameter,
+            handler: Box::new(handler),
+        };
+        self.opcodes.insert(opcode, Arc::new(custom));
+        Ok(())
+    }
+
+    pub fn get(&self, opcode: i64) -> Option<&Arc<CustomOpcode<M>>> {
+        self.opcodes.get(&opcode)
+    }
+}
+
+impl<M: MemoryBackend> CustomOpcode<M> {
+    pub(crate) fn run(&self, operands: &mut Operands<M>) -> Result<Effect, ErrorKind> {
+        (self.handler)(operands)
+    }
+}
+
+// A custom instruction's view of its parameters, with modes already applied
+pub struct Operands<'a, M: MemoryBackend> {
+    pub(crate) intcode: &'a mut IntCode<M>,
+    pub(crate) modes: i64,
+    pub(crate) write_parameter: Option<usize>,
+}
+
+impl<'a, M: MemoryBackend> Operands<'a, M> {
+    // The value of parameter `index`, counting from 0
+    pub fn value(&self, index: usize) -> Result<i64, ErrorKind> {
+        self.intcode.get_value(index as i64 + 1, self.modes)
+    }
+
+    // Writes through the opcode's write parameter
+    pub fn store(&mut self, value: i64) -> Result<(), ErrorKind> {
+        let index = self.write_parameter.ok_or(ErrorKind::NoWriteParameter)?;
+        let address = self.intcode.get_store_index(index as i64 + 1, self.modes)?;
+        self.intcode.memory.store(address, value)
+    }
+
+    pub fn intcode(&self) -> &IntCode<M> {
+        self.intcode
+    }
+}
+
+// Decodes the parameters of a custom instruction the way `Instruction::decode` does
+pub(crate) fn parameters<M: MemoryBackend>(
+    intcode: &IntCode<M>,
+    address: i64,
+    custom: &CustomOpcode<M>,
+) -> Option<Vec<Parameter>> {
+    let mut modes = intcode.memory.read(address).ok()? / 100;
+    let mut parameters = Vec::new();
+    for i in 0..custom.parameter_count {
+        let mode = Mode::from_value(modes % 10)?;
+        if mode == Mode::Immediate && custom.write_parameter == Some(i) {
+            return None;
+        }
+        let value = intcode.memory.read(address + i as i64 + 1).ok()?;
+        parameters.push(Parameter { mode, value });
+        modes /= 10;
+    }
+    Some(parameters)
+}
+
+#[test]
+fn test_custom_opcodes() {
+    use crate::intcode::compiled::CompiledIntCode;
+    use crate::intcode::{IntcodeError, RunState};
+    use std::sync::Mutex;
+
+    let printed = Arc::new(Mutex::new(Vec::new()));
+    let exit_code = Arc::new(Mutex::new(None));
+    // A debug print, an absolute value and a halt with an exit code
+    let mut table = OpcodeTable::new();
+    let log = printed.clone();
+    let code = exit_code.clone();
+    table
+        .register(50, 1, None, move |operands| {
+            log.lock().unwrap().push(operands.value(0)?);
+            Ok(Effect::Continue)
+        })
+        .unwrap();
+    table
+        .register(51, 2, Some(1), |operands| {
+            let value = operands.value(0)?.abs();
+            operands.store(value)?;
+            Ok(Effect::Continue)
+        })
+        .unwrap();
+    table
+        .register(98, 1, None, move |operands| {
+            *code.lock().unwrap() = Some(operands.value(0)?);
+            Ok(Effect::Halt)
+        })
+        .unwrap();
+    // Storing without a write parameter fails the step rather than the VM
+    table
+        .register(52, 1, None, |operands| {
+            operands.store(1)?;
+            Ok(Effect::Continue)
+        })
+        .unwrap();
+    assert_eq!(
+        table.register(7, 0, None, |_| Ok(Effect::Continue)),
+        Err(RegisterError::Reserved(7))
+    );
+    assert_eq!(
+        table.register(100, 0, None, |_| Ok(Effect::Continue)),
+        Err(RegisterError::Reserved(100))
+    );
+    assert_eq!(
+        table.register(53, 1, Some(1), |_| Ok(Effect::Continue)),
+        Err(RegisterError::WriteParameter {
+            write_parameter: 1,
+            parameter_count: 1
+        })
+    );
+    let table = Arc::new(table);
+
+    // ABS -7 into [10], print it, output it, exit with 3
+    let program = vec![151, -7, 10, 50, 10, 4, 10, 198, 3, 0, 0];
+    let mut code = IntCode::new(&program);
+    code.set_opcodes(table.clone());
+    assert_eq!(code.run_until_blocked(), Ok((vec![7], RunState::Halted)));
+    assert_eq!(*printed.lock().unwrap(), vec![7]);
+    assert_eq!(*exit_code.lock().unwrap(), Some(3));
+    assert!(code.finished);
+
+    let mut store = IntCode::new(&vec![52, 0]);
+    store.set_opcodes(table.clone());
+    assert_eq!(
+        store.run(),
+        Err(IntcodeError {
+            program_counter: 0,
+            instruction: 52,
+            kind: ErrorKind::NoWriteParameter
+        })
+    );
+
+    // The pre-decoded executor leaves custom opcodes to the interpreter
+    let mut compiled = IntCode::new(&program);
+    compiled.set_opcodes(table);
+    let mut compiled = CompiledIntCode::compile(compiled);
+    assert_eq!(
+        compiled.run_until_blocked(),
+        Ok((vec![7], RunState::Halted))
+    );
+
+    // Without the table they're still invalid
+    let mut plain = IntCode::new(&program);
+    assert_eq!(
+        plain.run(),
+        Err(IntcodeError {
+            program_counter: 0,
+            instruction: 151,
+            kind: ErrorKind::InvalidOpcode(51)
+        })
+    );
+}
@@ -0,0 +1,142 @@
+use crate::intcode::topology::Topology;
+use crate::intcode::IntcodeError;
+use permutator::heap_permutation;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainMode {
+    // Each amplifier runs once and feeds the next
+    Linear,
+    // The last amplifier feeds the first one until they all halt
+    Feedback,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseResult {
+    pub phases: Vec<i64>,
+    pub thrust: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PhaseError {
+    NoPhases,
+    Intcode(IntcodeError),
+}
+
+impl fmt::Display for PhaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhaseError::NoPhases => write!(f, "no phases to search"),
+            PhaseError::Intcode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for PhaseError {}
+
+impl From<IntcodeError> for PhaseError {
+    fn from(e: IntcodeError) -> PhaseError {
+        PhaseError::Intcode(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PhaseSearch {
+    pub best: PhaseResult,
+    // One entry per ordering, in the order the orderings were generated
+    pub results: Vec<PhaseResult>,
+}
+
+// The signal that comes out of the last amplifier when its phases are set to `phases`
+pub fn chain_thrust(program: &[i64], phases: &[i64], mode: ChainMode) -> Result<i64, IntcodeError> {
+    let mut inputs: Vec<Vec<i64>> = phases.iter().map(|phase| vec![*phase]).collect();
+    inputs[0].push(0);
+    let mut amplifiers: Topology = match mode {
+        ChainMode::Linear => Topology::chain(program, &inputs),
+        ChainMode::Feedback => Topology::ring(program, &inputs),
+    };
+    amplifiers.run()?;
+    Ok(amplifiers.node(phases.len() - 1).last_output)
+}
+
+// Every ordering of `values`, starting with `values` itself. permutator only calls back
+// with the orderings it swaps into, so the one it starts from is added first
+fn permutations(values: &[i64]) -> Vec<Vec<i64>> {
+    let mut orderings = vec![values.to_vec()];
+    heap_permutation(&mut values.to_vec(), |p| orderings.push(p.to_vec()));
+    orderings
+}
+
+// Tries every ordering of `phases` on `threads` worker threads
+pub fn search_phases(
+    program: &[i64],
+    phases: &[i64],
+    mode: ChainMode,
+    threads: usize,
+) -> Result<PhaseSearch, PhaseError> {
+    if phases.is_empty() {
+        return Err(PhaseError::NoPhases);
+    }
+    let orderings = permutations(phases);
+
+    // Workers take the next untried ordering until there are none left
+    let next = AtomicUsize::new(0);
+    let thrusts = Mutex::new(vec![None; orderings.len()]);
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= orderings.len() {
+                    break;
+                }
+                let thrust = chain_thrust(program, &orderings[index], mode);
+                thrusts.lock().unwrap()[index] = Some(thrust);
+            });
+        }
+    });
+
+    let mut results = Vec::with_capacity(orderings.len());
+    for (phases, thrust) in orderings.into_iter().zip(thrusts.into_inner().unwrap()) {
+        results.push(PhaseResult {
+            phases,
+            thrust: thrust.unwrap()?,
+        });
+    }
+    let best = results.iter().max_by_key(|r| r.thrust).unwrap().clone();
+    Ok(PhaseSearch { best, results })
+}
+
+#[test]
+fn test_search_phases() {
+    let program = vec![
+        3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+    ];
+    let search = search_phases(&program, &[0, 1, 2, 3, 4], ChainMode::Linear, 3).unwrap();
+    assert_eq!(search.results.len(), 120);
+    assert_eq!(
+        search.results[0],
+        PhaseResult {
+            phases: vec![0, 1, 2, 3, 4],
+            thrust: 1234
+        }
+    );
+    assert_eq!(search.best.thrust, 43210);
+    assert_eq!(search.best.phases, vec![4, 3, 2, 1, 0]);
+
+    let program = vec![
+        3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+        1005, 28, 6, 99, 0, 0, 5,
+    ];
+    let search = search_phases(&program, &[5, 6, 7, 8, 9], ChainMode::Feedback, 2).unwrap();
+    assert_eq!(search.best.thrust, 139629729);
+    assert_eq!(search.best.phases, vec![9, 8, 7, 6, 5]);
+
+    assert!(search_phases(&[42], &[1, 2], ChainMode::Linear, 2).is_err());
+    assert_eq!(
+        search_phases(&[99], &[], ChainMode::Linear, 2).unwrap_err(),
+        PhaseError::NoPhases
+    );
+}
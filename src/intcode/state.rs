@@ -0,0 +1,255 @@
+use crate::intcode::{IntCode, IntInput, MemoryBackend};
+use core::fmt;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+// Saved machines are plain text so they can be diffed and attached to bug reports:
+//
+//     intcode-state 1
+//     program_counter 2
+//     relative_base 0
+//     finished 0
+//     last_output 0
+//     input 1,2
+//     memory 0 3,11,1,11,12
+//     memory 2048 5
+//
+// Each memory line is a start address followed by a run of cells, zero cells are omitted.
+
+pub const STATE_VERSION: u32 = 1;
+
+const HEADER: &str = "intcode-state";
+// Gaps of zero cells shorter than this are written out instead of starting a new run
+const MAX_GAP: i64 = 16;
+
+#[derive(Debug)]
+pub enum StateError {
+    Io(io::Error),
+    Version(u32),
+    Format { line: usize, message: String },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Io(e) => write!(f, "{}", e),
+            StateError::Version(v) => write!(
+                f,
+                "unsupported state version {} (expected {})",
+                v, STATE_VERSION
+            ),
+            StateError::Format { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl From<io::Error> for StateError {
+    fn from(e: io::Error) -> StateError {
+        StateError::Io(e)
+    }
+}
+
+fn join(values: &[i64]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+impl<M: MemoryBackend> IntCode<M> {
+    pub fn save<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "{} {}", HEADER, STATE_VERSION)?;
+        writeln!(w, "program_counter {}", self.program_counter)?;
+        writeln!(w, "relative_base {}", self.relative_base)?;
+        writeln!(w, "finished {}", self.finished as u8)?;
+        writeln!(w, "last_output {}", self.last_output)?;
+        writeln!(
+            w,
+            "{}",
+            format!("input {}", join(self.input.pending())).trim_end()
+        )?;
+
+        let mut runs: Vec<(i64, Vec<i64>)> = Vec::new();
+        for (address, value) in self.memory.cells() {
+            if let Some((start, values)) = runs.last_mut() {
+                let end = *start + values.len() as i64;
+                if address - end < MAX_GAP {
+                    values.resize((address - *start) as usize, 0);
+                    values.push(value);
+                    continue;
+                }
+            }
+            runs.push((address, vec![value]));
+        }
+        for (start, values) in runs {
+            writeln!(w, "memory {} {}", start, join(&values))?;
+        }
+        Ok(())
+    }
+
+    pub fn load<R: Read>(r: R) -> Result<IntCode<M>, StateError> {
+        let mut intcode: IntCode<M> = IntCode::with_backend(&[]);
+        let mut found_header = false;
+
+        for (index, line) in BufReader::new(r).lines().enumerate() {
+            let line = line?;
+            let error = |message: &str| StateError::Format {
+                line: index + 1,
+                message: message.to_string(),
+            };
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() {
+                continue;
+            }
+            let number = |i: usize| -> Result<i64, StateError> {
+                parts
+                    .get(i)
+                    .and_then(|p| p.parse().ok())
+                    .ok_or_else(|| error(&format!("expected a number after {}", parts[0])))
+            };
+            let list = |i: usize| -> Result<Vec<i64>, StateError> {
+                match parts.get(i) {
+                    Some(p) => p
+                        .split(',')
+                        .filter(|v| !v.is_empty())
+                        .map(|v| v.parse().map_err(|_| error("invalid value list")))
+                        .collect(),
+                    None => Err(error("missing value list")),
+                }
+            };
+
+            if !found_header {
+                if parts[0] != HEADER {
+                    return Err(error("not an Intcode state file"));
+                }
+                let version =
+                    u32::try_from(number(1)?).map_err(|_| error("state version out of range"))?;
+                if version != STATE_VERSION {
+                    return Err(StateError::Version(version));
+                }
+                found_header = true;
+                continue;
+            }
+
+            match parts[0] {
+                "program_counter" => intcode.program_counter = number(1)?,
+                "relative_base" => intcode.relative_base = number(1)?,
+                "finished" => intcode.finished = number(1)? != 0,
+                "last_output" => intcode.last_output = number(1)?,
+                "input" => {
+                    // An empty queue leaves the list out entirely
+                    let values = match parts.get(1) {
+                        None => Vec::new(),
+                        Some(_) => list(1)?,
+                    };
+                    intcode.input = IntInput::new();
+                    for value in values {
+                        intcode.input.push(value);
+                    }
+                }
+                "memory" => {
+                    let start = number(1)?;
+                    for (i, value) in list(2)?.into_iter().enumerate() {
+                        intcode
+                            .memory
+                            .store(
+                                start
+                                    .checked_add(i as i64)
+                                    .ok_or_else(|| error("memory address out of range"))?,
+                                value,
+                            )
+                            .map_err(|e| error(&e.to_string()))?;
+                    }
+                }
+                other => return Err(error(&format!("unknown entry {}", other))),
+            }
+        }
+
+        if !found_header {
+            return Err(StateError::Format {
+                line: 1,
+                message: "empty state file".to_string(),
+            });
+        }
+        Ok(intcode)
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, p: P) -> io::Result<()> {
+        self.save(File::create(p)?)
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(p: P) -> Result<IntCode<M>, StateError> {
+        IntCode::load(File::open(p)?)
+    }
+}
+
+#[test]
+fn test_save_and_load() {
+    use crate::intcode::{HashMemory, RunState};
+
+    // Adds up every input, printing the running total
+    let program = vec![3, 11, 1, 11, 12, 12, 4, 12, 1105, 1, 0, 0, 0];
+    let mut code = IntCode::new(&program);
+    code.input.push(5);
+    code.input.push(10);
+    code.input.push(20);
+    assert_eq!(code.run_for_outputs(1), Ok(vec![5]));
+    code.memory.store(5000, -3).unwrap();
+
+    let mut buffer = Vec::new();
+    code.save(&mut buffer).unwrap();
+    let text = String::from_utf8(buffer.clone()).unwrap();
+    assert!(text.starts_with("intcode-state 1\nprogram_counter 8\n"));
+    assert!(text.contains("input 10,20\n"));
+    assert!(text.contains("memory 0 3,11,1,11,12,12,4,12,1105,1,0,5,5\nmemory 5000 -3\n"));
+
+    let mut restored: IntCode<HashMemory> = IntCode::load(&buffer[..]).unwrap();
+    assert_eq!(restored.memory.read(5000), Ok(-3));
+    assert_eq!(restored.last_output, 5);
+    assert_eq!(restored.run_for_outputs(2), code.run_for_outputs(2));
+    assert_eq!(restored.run(), Ok(RunState::AwaitingInput));
+}
+
+#[test]
+fn test_load_errors() {
+    let load = |text: &str| IntCode::<crate::intcode::PagedMemory>::load(text.as_bytes());
+
+    assert!(matches!(
+        load("intcode-state 2\n"),
+        Err(StateError::Version(2))
+    ));
+    assert!(matches!(
+        load("not a state\n"),
+        Err(StateError::Format { line: 1, .. })
+    ));
+    assert!(matches!(
+        load("intcode-state 1\nprogram_counter x\n"),
+        Err(StateError::Format { line: 2, .. })
+    ));
+    assert!(matches!(
+        load("intcode-state 1\nmemory -4 1,2\n"),
+        Err(StateError::Format { line: 2, .. })
+    ));
+    assert!(matches!(load(""), Err(StateError::Format { .. })));
+    assert!(matches!(
+        load("intcode-state 4294967297\n"),
+        Err(StateError::Format { line: 1, .. })
+    ));
+    assert!(matches!(
+        load("intcode-state -1\n"),
+        Err(StateError::Format { line: 1, .. })
+    ));
+    assert!(matches!(
+        load("intcode-state 1\nmemory 9223372036854775807 1,2\n"),
+        Err(StateError::Format { line: 2, .. })
+    ));
+
+    let code = load("intcode-state 1\ninput\nmemory 0 99\n").unwrap();
+    assert!(code.input.pending().is_empty());
+}
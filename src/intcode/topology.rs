@@ -0,0 +1,142 @@
+use crate::intcode::{IntCode, IntcodeError, MemoryBackend, PagedMemory};
+
+// Computers wired output to input. Every output of a node is copied to the input of each
+// node it's connected to, so chains, rings and fan-outs are all built with `connect`
+pub struct Topology<M: MemoryBackend = PagedMemory> {
+    nodes: Vec<IntCode<M>>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl<M: MemoryBackend> Default for Topology<M> {
+    fn default() -> Topology<M> {
+        Topology {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<M: MemoryBackend> Topology<M> {
+    pub fn new() -> Topology<M> {
+        Topology::default()
+    }
+
+    // One node per entry of `inputs`, each running `program` and feeding the next
+    pub fn chain(program: &[i64], inputs: &[Vec<i64>]) -> Topology<M> {
+        let mut topology = Topology::new();
+        for (i, initial) in inputs.iter().enumerate() {
+            topology.add_node(program, initial);
+            if i > 0 {
+                topology.connect(i - 1, i);
+            }
+        }
+        topology
+    }
+
+    // A chain whose last node feeds the first one
+    pub fn ring(program: &[i64], inputs: &[Vec<i64>]) -> Topology<M> {
+        let mut topology = Topology::chain(program, inputs);
+        if !inputs.is_empty() {
+            topology.connect(inputs.len() - 1, 0);
+        }
+        topology
+    }
+
+    // Returns the id used to connect the node
+    pub fn add_node(&mut self, program: &[i64], initial_inputs: &[i64]) -> usize {
+        let mut intcode = IntCode::with_backend(program);
+        for value in initial_inputs {
+            intcode.input.push(*value);
+        }
+        self.nodes.push(intcode);
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.edges[from].push(to);
+    }
+
+    pub fn node(&self, id: usize) -> &IntCode<M> {
+        &self.nodes[id]
+    }
+
+    // Runs the nodes in turn until every one has halted or is waiting for input nobody
+    // will send. Returns everything each node printed, indexed by node id
+    pub fn run(&mut self) -> Result<Vec<Vec<i64>>, IntcodeError> {
+        let mut outputs = vec![Vec::new(); self.nodes.len()];
+        let mut first_round = true;
+        loop {
+            let mut progress = false;
+            for (id, printed) in outputs.iter_mut().enumerate() {
+                let node = &mut self.nodes[id];
+                if node.finished || (!first_round && !node.input.has_input()) {
+                    continue;
+                }
+                progress = true;
+                let (values, _) = node.run_until_blocked()?;
+                for value in values {
+                    for to in &self.edges[id] {
+                        self.nodes[*to].input.push(value);
+                    }
+                    printed.push(value);
+                }
+            }
+            if !progress {
+                return Ok(outputs);
+            }
+            first_round = false;
+        }
+    }
+}
+
+#[test]
+fn test_topology() {
+    use crate::intcode::asm::assemble;
+
+    // Prints every input plus one
+    let program = assemble(
+        "
+        loop:   IN [x]
+                ADD [x], #1, [x]
+                OUT [x]
+                JZ #0, #loop
+        x:      DATA 0
+        ",
+    )
+    .unwrap();
+
+    let mut chain: Topology = Topology::chain(&program, &[vec![0, 10], vec![], vec![]]);
+    assert_eq!(chain.run(), Ok(vec![vec![1, 11], vec![2, 12], vec![3, 13]]));
+
+    // 0 feeds 1 and 2, which both feed 3
+    let mut diamond: Topology = Topology::new();
+    for initial in &[vec![5], vec![], vec![], vec![]] {
+        diamond.add_node(&program, initial);
+    }
+    diamond.connect(0, 1);
+    diamond.connect(0, 2);
+    diamond.connect(1, 3);
+    diamond.connect(2, 3);
+    assert_eq!(diamond.run().unwrap()[3], vec![8, 8]);
+    assert!(!diamond.node(3).finished);
+
+    // Counts down around a ring of three until it reaches zero
+    let program = assemble(
+        "
+        loop:   IN [x]
+                JZ [x], #end
+                ADD [x], #-1, [x]
+                OUT [x]
+                JZ #0, #loop
+        end:    HLT
+        x:      DATA 0
+        ",
+    )
+    .unwrap();
+    let mut ring: Topology = Topology::ring(&program, &[vec![7], vec![], vec![]]);
+    let outputs = ring.run().unwrap();
+    assert_eq!(outputs, vec![vec![6, 3, 0], vec![5, 2], vec![4, 1]]);
+    assert!(ring.node(1).finished);
+    assert!(!ring.node(2).finished);
+}
@@ -0,0 +1,194 @@
+use crate::intcode::instruction::Opcode;
+use core::fmt;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub program_counter: i64,
+    pub relative_base: i64,
+    pub instruction: i64,
+    // Values read by the instruction, or the target address for the parameter it writes
+    pub operands: Vec<i64>,
+    pub write: Option<(i64, i64)>,
+}
+
+impl TraceEvent {
+    pub fn opcode(&self) -> i64 {
+        self.instruction % 100
+    }
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mnemonic = Opcode::from_value(self.opcode())
+            .map(|o| o.mnemonic())
+            .unwrap_or("???");
+        write!(
+            f,
+            "{:04} rb={} {}",
+            self.program_counter, self.relative_base, mnemonic
+        )?;
+        if !self.operands.is_empty() {
+            let operands: Vec<String> = self.operands.iter().map(|v| v.to_string()).collect();
+            write!(f, " {}", operands.join(","))?;
+        }
+        if let Some((address, value)) = self.write {
+            write!(f, " [{}]={}", address, value)?;
+        }
+        Ok(())
+    }
+}
+
+// Called by the VM after every executed instruction
+pub trait Tracer: Send {
+    fn trace(&mut self, event: &TraceEvent);
+}
+
+// Keeps the last `capacity` instructions
+pub struct RingTracer {
+    capacity: usize,
+    events: VecDeque<TraceEvent>,
+}
+
+impl RingTracer {
+    pub fn new(capacity: usize) -> RingTracer {
+        RingTracer {
+            capacity,
+            events: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter()
+    }
+}
+
+impl Tracer for RingTracer {
+    fn trace(&mut self, event: &TraceEvent) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event.clone());
+    }
+}
+
+// Writes one line per instruction, meant to be diffed between runs
+pub struct WriterTracer<W: Write + Send> {
+    writer: W,
+}
+
+impl<W: Write + Send> WriterTracer<W> {
+    pub fn new(writer: W) -> WriterTracer<W> {
+        WriterTracer { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write + Send> Tracer for WriterTracer<W> {
+    fn trace(&mut self, event: &TraceEvent) {
+        // A trace that can't be written isn't worth aborting the program for
+        let _ = writeln!(self.writer, "{}", event);
+    }
+}
+
+#[derive(Default)]
+pub struct Profiler {
+    pub instructions: u64,
+    pub hits: HashMap<i64, u64>,
+    pub opcodes: HashMap<i64, u64>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    // Addresses sorted by how many times they were executed, hottest first
+    pub fn hottest(&self) -> Vec<(i64, u64)> {
+        let mut hits: Vec<(i64, u64)> = self.hits.iter().map(|(a, c)| (*a, *c)).collect();
+        hits.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hits
+    }
+
+    pub fn report(&self, top: usize) -> String {
+        let mut lines = vec![format!("{} instructions executed", self.instructions)];
+
+        lines.push("Opcodes:".to_string());
+        let mut opcodes: Vec<(i64, u64)> = self.opcodes.iter().map(|(o, c)| (*o, *c)).collect();
+        opcodes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (opcode, count) in opcodes {
+            let mnemonic = Opcode::from_value(opcode)
+                .map(|o| o.mnemonic())
+                .unwrap_or("???");
+            lines.push(format!(
+                "  {:<4} {:>12} {:>6.2}%",
+                mnemonic,
+                count,
+                100.0 * count as f64 / self.instructions as f64
+            ));
+        }
+
+        lines.push(format!("Hottest {} addresses:", top));
+        for (address, count) in self.hottest().into_iter().take(top) {
+            lines.push(format!("  {:04} {:>12}", address, count));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Tracer for Profiler {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.instructions += 1;
+        *self.hits.entry(event.program_counter).or_insert(0) += 1;
+        *self.opcodes.entry(event.opcode()).or_insert(0) += 1;
+    }
+}
+
+#[test]
+fn test_tracers() {
+    use crate::intcode::IntCode;
+    use std::sync::{Arc, Mutex};
+
+    // Counts down from 3, printing each value
+    let program = vec![4, 10, 1001, 10, -1, 10, 1005, 10, 0, 99, 3];
+    let ring = Arc::new(Mutex::new(RingTracer::new(2)));
+    let profiler = Arc::new(Mutex::new(Profiler::new()));
+    let writer = Arc::new(Mutex::new(WriterTracer::new(Vec::new())));
+
+    for tracer in 0..3 {
+        let mut code = IntCode::new(&program);
+        match tracer {
+            0 => code.set_tracer(ring.clone()),
+            1 => code.set_tracer(profiler.clone()),
+            _ => code.set_tracer(writer.clone()),
+        }
+        assert_eq!(code.run_until_blocked().unwrap().0, vec![3, 2, 1]);
+    }
+
+    let ring = ring.lock().unwrap();
+    let last: Vec<String> = ring.events().map(|e| e.to_string()).collect();
+    assert_eq!(last, vec!["0006 rb=0 JNZ 0,0", "0009 rb=0 HLT"]);
+
+    let profiler = profiler.lock().unwrap();
+    assert_eq!(profiler.instructions, 10);
+    assert_eq!(profiler.hottest()[0], (0, 3));
+    assert_eq!(profiler.opcodes[&4], 3);
+    assert!(profiler
+        .report(3)
+        .starts_with("10 instructions executed\nOpcodes:\n  ADD "));
+
+    let writer = writer.lock().unwrap();
+    let text = String::from_utf8(writer.writer.clone()).unwrap();
+    assert_eq!(
+        text.lines().take(3).collect::<Vec<&str>>(),
+        vec![
+            "0000 rb=0 OUT 3",
+            "0002 rb=0 ADD 3,-1,10 [10]=2",
+            "0006 rb=0 JNZ 2,0"
+        ]
+    );
+}
@@ -0,0 +1,6 @@
+pub mod answers;
+pub mod coords;
+pub mod intcode;
+pub mod parse;
+pub mod solutions;
+pub mod solver;
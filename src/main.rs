@@ -1,16 +1,403 @@
-mod coords;
-mod intcode;
-mod solutions;
-mod solver;
-
-use crate::solutions::exec_day;
+use adv_rs_2019::answers::{answers_file, check, Answers, Verdict, ANSWERS_DIR};
+use adv_rs_2019::solutions::{exec_day, DAYS};
+use adv_rs_2019::solver::{
+    format_duration, input_file, json_record, panic_message, set_diagnostics_to_stderr, InputError,
+    Part, Report, STDIN,
+};
 use std::env;
+use std::panic;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str =
+    "Usage: adv-rs-2019 [run] <days> [--part 1|2] [--input <file>|-] [--format text|json]
+       adv-rs-2019 [run] --all [--part 1|2] [--format text|json]
+       adv-rs-2019 bench <days>|--all [--runs n] [--part 1|2] [--input <file>]
+       adv-rs-2019 verify <days>|--all [--part 1|2] [--input <file>|-] [--answers <dir>] [--save]
+
+<days> is a day, a range like 1..25 or a comma separated list of both.
+--input reads a single day's input from <file>, or from stdin with -.
+--format json prints one JSON object per line and day, with the answers, timings and error.
+bench solves each day n times, 10 by default, and prints a table of timings.
+verify compares the answers with the ones in <dir>/dayNN.toml, answers/ by default,
+and --save records the answers that weren't there yet. Unrecorded answers fail without it.
+
+Exits with 1 if any day fails or gives a wrong answer and 2 on bad arguments.";
+
+#[derive(PartialEq)]
+enum Command {
+    Run,
+    Bench,
+    Verify,
+}
+
+struct Options {
+    command: Command,
+    days: Vec<i32>,
+    part: Part,
+    input: Option<String>,
+    runs: usize,
+    answers: String,
+    save: bool,
+    json: bool,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
+
+// Days like "3", "1..25" or "1,4,10..12", each checked against the solved ones
+fn parse_days(spec: &str) -> Result<Vec<i32>, String> {
+    let mut days = Vec::new();
+    for item in spec.split(',') {
+        let day = |s: &str| {
+            s.trim()
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid day '{}'", s))
+        };
+        match item.find("..") {
+            Some(i) => {
+                let (from, to) = (day(&item[..i])?, day(&item[i + 2..])?);
+                if from > to {
+                    return Err(format!("Empty day range '{}'", item));
+                }
+                days.extend(from..=to);
+            }
+            None => days.push(day(item)?),
+        }
+    }
+    if let Some(day) = days.iter().find(|d| !DAYS.contains(d)) {
+        return Err(format!("Day {} hasn't been solved yet :(", day));
+    }
+    Ok(days)
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut days = None;
+    let mut part = Part::Both;
+    let mut input = None;
+    let mut runs = 10;
+    let mut answers = String::from(ANSWERS_DIR);
+    let mut save = false;
+    let mut json = false;
+
+    let (command, args) = match args.first().map(String::as_str) {
+        Some("run") => (Command::Run, &args[1..]),
+        Some("bench") => (Command::Bench, &args[1..]),
+        Some("verify") => (Command::Verify, &args[1..]),
+        _ => (Command::Run, args),
+    };
+    let mut i = 0;
+    while i < args.len() {
+        let value = || args.get(i + 1).ok_or_else(|| USAGE.to_string());
+        match args[i].as_str() {
+            "--all" => {
+                days = Some(DAYS.to_vec());
+                i += 1;
+                continue;
+            }
+            "--save" if command == Command::Verify => {
+                save = true;
+                i += 1;
+                continue;
+            }
+            "--answers" if command == Command::Verify => answers = value()?.clone(),
+            "--part" => {
+                part = match value()?.as_str() {
+                    "1" => Part::First,
+                    "2" => Part::Second,
+                    p => return Err(format!("Invalid part '{}', expected 1 or 2", p)),
+                }
+            }
+            "--input" => input = Some(value()?.clone()),
+            "--format" if command == Command::Run => {
+                json = match value()?.as_str() {
+                    "text" => false,
+                    "json" => true,
+                    f => return Err(format!("Invalid format '{}', expected text or json", f)),
+                }
+            }
+            "--runs" if command == Command::Bench => {
+                runs = match value()?.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("Invalid number of runs '{}'", value()?)),
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            spec if days.is_none() && !spec.starts_with("--") => {
+                days = Some(parse_days(spec)?);
+                i += 1;
+                continue;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+        i += 2;
+    }
+
+    let days = days.ok_or_else(|| USAGE.to_string())?;
+    if input.is_some() && days.len() != 1 {
+        return Err(String::from("--input needs exactly one day"));
+    }
+    if command == Command::Bench && input.as_deref() == Some(STDIN) {
+        return Err(String::from(
+            "bench reads the input on every run, it can't use stdin",
+        ));
+    }
+    Ok(Options {
+        command,
+        days,
+        part,
+        input,
+        runs,
+        answers,
+        save,
+        json,
+    })
+}
+
+// Panics in either part are caught by `Solver::run`, this catches the ones while parsing.
+// A solver that panics has already printed why, the outer error only carries the message
+fn exec(day: i32, input: &str, part: Part) -> Result<Result<Report, InputError>, String> {
+    panic::catch_unwind(|| exec_day(day, input, part)).map_err(|p| panic_message(p.as_ref()))
+}
+
+// Returns whether every day succeeded
+fn run(options: &Options) -> bool {
+    set_diagnostics_to_stderr(options.json);
+    let mut ok = true;
+    for &day in &options.days {
+        let input = options.input.clone().unwrap_or_else(|| input_file(day));
+        if options.json {
+            let result = match exec(day, &input, options.part) {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(message) => Err(format!("solver panicked: {}", message)),
+            };
+            ok &= result.as_ref().map_or(false, |r| r.error().is_none());
+            println!("{}", json_record(day, &input, &result));
+            continue;
+        }
+
+        if options.days.len() > 1 {
+            println!("Day {}", day);
+        }
+        match exec(day, &input, options.part) {
+            Ok(Ok(report)) => {
+                print!("{}", report);
+                ok &= report.error().is_none();
+            }
+            Ok(Err(e)) => {
+                eprintln!("{}", e);
+                ok = false;
+            }
+            Err(_) => ok = false,
+        }
+    }
+    ok
+}
+
+// Timings of every run of a day, sorted so the median is in the middle
+struct Timings {
+    parse: Vec<Duration>,
+    first: Vec<Duration>,
+    second: Vec<Duration>,
+    total: Vec<Duration>,
+    // Intcode instructions per run
+    cycles: u64,
+    // Why the runs stopped early, the timings cover the runs before
+    error: Option<String>,
+}
+
+// `-` for a day without any timing
+fn time(time: Option<&Duration>) -> String {
+    time.map_or_else(|| String::from("-"), |t| format_duration(*t))
+}
+
+fn median(times: &[Duration]) -> String {
+    time(times.get(times.len() / 2))
+}
+
+fn bench_day(day: i32, input: &str, part: Part, runs: usize) -> Timings {
+    let mut reports = Vec::new();
+    let mut error = None;
+    for _ in 0..runs {
+        match exec(day, input, part) {
+            Ok(Ok(report)) => match report.error() {
+                Some(e) => error = Some(format!("day {} {}", day, e)),
+                None => reports.push(report),
+            },
+            Ok(Err(e)) => error = Some(e.to_string()),
+            Err(message) => error = Some(format!("day {} panicked: {}", day, message)),
+        }
+        if error.is_some() {
+            break;
+        }
+    }
+    let sorted = |time: &dyn Fn(&Report) -> Option<Duration>| {
+        let mut times: Vec<Duration> = reports.iter().flat_map(time).collect();
+        times.sort();
+        times
+    };
+    Timings {
+        parse: sorted(&|r| Some(r.parse_time)),
+        first: sorted(&|r| r.first.as_ref().map(|a| a.time)),
+        second: sorted(&|r| r.second.as_ref().map(|a| a.time)),
+        total: sorted(&|r| Some(r.total_time())),
+        cycles: reports.get(reports.len() / 2).map_or(0, |r| r.cycles),
+        error,
+    }
+}
+
+fn bench(options: &Options) -> bool {
+    let mut ok = true;
+    let mut rows = Vec::new();
+    for &day in &options.days {
+        eprintln!("Benchmarking day {}", day);
+        let input = options.input.clone().unwrap_or_else(|| input_file(day));
+        let timings = bench_day(day, &input, options.part, options.runs);
+        if let Some(e) = &timings.error {
+            eprintln!("{}", e);
+            ok = false;
+        }
+        rows.push((day, timings));
+    }
+
+    // Failed days keep their row, with whatever runs finished before the failure
+    println!(
+        "{:>3}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>13}  Status",
+        "Day", "Parse", "Part 1", "Part 2", "Min", "Median", "Max", "Instructions"
+    );
+    for (day, t) in &rows {
+        let cycles = match t.cycles {
+            0 => String::from("-"),
+            n => n.to_string(),
+        };
+        let status = match &t.error {
+            Some(_) => format!("FAIL after {} of {} runs", t.total.len(), options.runs),
+            None => String::from("ok"),
+        };
+        println!(
+            "{:>3}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>13}  {}",
+            day,
+            median(&t.parse),
+            median(&t.first),
+            median(&t.second),
+            time(t.total.first()),
+            median(&t.total),
+            time(t.total.last()),
+            cycles,
+            status
+        );
+    }
+    let complete: Vec<&Timings> = rows
+        .iter()
+        .map(|(_, t)| t)
+        .filter(|t| t.error.is_none())
+        .collect();
+    let total: Duration = complete.iter().map(|t| t.total[t.total.len() / 2]).sum();
+    println!(
+        "Sum of medians over {} of {} days: {}",
+        complete.len(),
+        rows.len(),
+        format_duration(total)
+    );
+    ok
+}
+
+// Returns whether every day ran and all of its answers matched, or were saved as new ones
+fn verify(options: &Options) -> bool {
+    let (mut passed, mut failed, mut new) = (0, 0, 0);
+    let mut ok = true;
+    for &day in &options.days {
+        let path = answers_file(&options.answers, day);
+        let mut answers = match Answers::load(&path) {
+            Ok(answers) => answers,
+            Err(e) => {
+                eprintln!("{}", e);
+                ok = false;
+                continue;
+            }
+        };
+        let input = options.input.clone().unwrap_or_else(|| input_file(day));
+        let report = match exec(day, &input, options.part) {
+            Ok(Ok(report)) => report,
+            Ok(Err(e)) => {
+                eprintln!("{}", e);
+                ok = false;
+                continue;
+            }
+            Err(_) => {
+                ok = false;
+                continue;
+            }
+        };
+
+        let mut recorded = false;
+        let parts = [
+            (1, &report.first, &mut answers.first),
+            (2, &report.second, &mut answers.second),
+        ];
+        for (n, answer, expected) in parts {
+            let answer = match answer.as_ref().map(|a| &a.value) {
+                Some(Ok(answer)) => answer,
+                Some(Err(message)) => {
+                    failed += 1;
+                    println!("Day {:>2} part {}: FAIL panicked: {}", day, n, message);
+                    continue;
+                }
+                None => continue,
+            };
+            match check(expected.as_deref(), answer) {
+                Verdict::Pass => {
+                    passed += 1;
+                    println!("Day {:>2} part {}: PASS {}", day, n, answer);
+                }
+                Verdict::Fail => {
+                    failed += 1;
+                    println!(
+                        "Day {:>2} part {}: FAIL expected {}, got {}",
+                        day,
+                        n,
+                        expected.as_deref().unwrap_or_default(),
+                        answer
+                    );
+                }
+                Verdict::New => {
+                    new += 1;
+                    println!("Day {:>2} part {}: NEW  {}", day, n, answer);
+                    *expected = Some(answer.clone());
+                    recorded = true;
+                }
+            }
+        }
+        if options.save && recorded {
+            if let Err(e) = answers.save(&path) {
+                eprintln!("Unable to save {}: {}", path, e);
+                ok = false;
+            }
+        }
+    }
+    println!("{} passed, {} failed, {} new", passed, failed, new);
+    // An answer with nothing to compare against proves nothing, unless it's being recorded
+    if new > 0 && !options.save {
+        eprintln!("Run with --save to record the new answers");
+    }
+    ok && failed == 0 && (new == 0 || options.save)
+}
 
 fn main() {
-    let day = env::args()
-        .nth(1)
-        .unwrap_or_else(|| String::from("1"))
-        .parse()
-        .unwrap_or(1);
-    exec_day(day);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse_args(&args).unwrap_or_else(|e| fail(&e));
+
+    let ok = match options.command {
+        Command::Run => run(&options),
+        Command::Bench => bench(&options),
+        Command::Verify => verify(&options),
+    };
+    if !ok {
+        process::exit(1);
+    }
 }
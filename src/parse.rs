@@ -0,0 +1,161 @@
+use core::fmt;
+use std::error::Error;
+use std::io;
+use std::str::FromStr;
+
+// Lines and columns count from 1. Parsers for a single line report line 1 and
+// `parse_lines` moves the error to the line it came from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(line: usize, column: usize, message: S) -> ParseError {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    // An error at `column` of the only line being parsed
+    pub fn at<S: Into<String>>(column: usize, message: S) -> ParseError {
+        ParseError::new(1, column, message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+// The whole input as text, pointing at the first byte that isn't UTF-8
+pub fn read_text<R: io::Read>(mut r: R) -> Result<String, ParseError> {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)
+        .map_err(|e| ParseError::at(1, format!("unable to read input: {}", e)))?;
+    String::from_utf8(bytes).map_err(|e| {
+        let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+        let text = String::from_utf8_lossy(valid);
+        let line = text.matches('\n').count() + 1;
+        let column = text.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        ParseError::new(line, column, "input is not valid UTF-8")
+    })
+}
+
+// Parses every line that isn't blank
+pub fn parse_lines<T, F>(text: &str, mut parse: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    let mut parsed = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        parsed.push(parse(line).map_err(|mut e| {
+            e.line += index;
+            e
+        })?);
+    }
+    Ok(parsed)
+}
+
+// The pieces of `line` between separators, trimmed, each with the column it starts at
+pub fn fields(line: &str, separator: char) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    for field in line.split(separator) {
+        let trimmed = field.trim();
+        let offset = field.len() - field.trim_start().len();
+        fields.push((column(line, start + offset), trimmed));
+        start += field.len() + separator.len_utf8();
+    }
+    fields
+}
+
+// The column of byte offset `offset` in `line`
+pub fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+pub fn parse_number<T: FromStr>(field: &str, column: usize) -> Result<T, ParseError> {
+    field
+        .parse()
+        .map_err(|_| ParseError::at(column, format!("expected a number, found '{}'", field)))
+}
+
+// Numbers separated by `separator`, possibly over several lines
+pub fn parse_separated<T: FromStr>(text: &str, separator: char) -> Result<Vec<T>, ParseError> {
+    let lines = parse_lines(text, |line| {
+        let mut fields = fields(line, separator);
+        // A separator at the end of a line continues on the next one
+        if fields.len() > 1 && matches!(fields.last(), Some((_, ""))) {
+            fields.pop();
+        }
+        fields
+            .into_iter()
+            .map(|(column, field)| parse_number(field, column))
+            .collect::<Result<Vec<T>, ParseError>>()
+    })?;
+    Ok(lines.into_iter().flatten().collect())
+}
+
+// Fails at the first character of `text` not in `allowed`
+pub fn check_characters(text: &str, allowed: &str) -> Result<(), ParseError> {
+    for (index, line) in text.lines().enumerate() {
+        if let Some((offset, c)) = line.char_indices().find(|(_, c)| !allowed.contains(*c)) {
+            return Err(ParseError::new(
+                index + 1,
+                column(line, offset),
+                format!("unexpected character '{}'", c),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_separated() {
+    assert_eq!(parse_separated::<i64>("1,-2, 3\n", ','), Ok(vec![1, -2, 3]));
+    assert_eq!(
+        parse_separated::<i64>("1,2,\n3,4\n\n", ','),
+        Ok(vec![1, 2, 3, 4])
+    );
+    assert_eq!(
+        parse_separated::<i64>("1,2,3\n4, x5", ','),
+        Err(ParseError::new(2, 4, "expected a number, found 'x5'"))
+    );
+    assert_eq!(parse_separated::<i64>("1,,3", ',').unwrap_err().column, 3);
+    assert_eq!(parse_separated::<u32>("123-456", '-'), Ok(vec![123, 456]));
+}
+
+#[test]
+fn test_parse_lines() {
+    let parsed = parse_lines("12\n\n 7\n", |l| parse_number::<i64>(l.trim(), 1));
+    assert_eq!(parsed, Ok(vec![12, 7]));
+    let error = parse_lines("1\n2\nthree\n", |l| parse_number::<i64>(l, 1)).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "line 3, column 1: expected a number, found 'three'"
+    );
+
+    assert_eq!(
+        check_characters("..#\n.#?", ".#"),
+        Err(ParseError::new(2, 3, "unexpected character '?'"))
+    );
+    assert_eq!(
+        read_text(&b"ab\nc\xffd"[..]),
+        Err(ParseError::new(2, 2, "input is not valid UTF-8"))
+    );
+}
@@ -1,5 +1,6 @@
+use crate::parse::{parse_lines, parse_number, read_text, ParseError};
 use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 pub struct Problem;
 
@@ -8,9 +9,8 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
-        let r = BufReader::new(r);
-        r.lines().flatten().flat_map(|l| l.parse()).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
+        parse_lines(&read_text(r)?, |l| parse_number(l.trim(), 1))
     }
 
     fn solve_first(&self, input: &Vec<i64>) -> i64 {
@@ -1,6 +1,7 @@
-use crate::intcode::{IntCode, IntInput};
+use crate::intcode::{read_input, IntCode, IntInput, IntcodeError, MemoryBackend};
+use crate::parse::ParseError;
 use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 pub struct Problem;
 
@@ -9,23 +10,18 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
-        let r = BufReader::new(r);
-        r.split(b',')
-            .flatten()
-            .filter_map(|v| String::from_utf8(v).ok())
-            .filter_map(|v| v.parse().ok())
-            .collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
+        read_input(r)
     }
 
     fn solve_first(&self, input: &Vec<i64>) -> i64 {
-        run_program(input, 12, 2)
+        run_program(input, 12, 2).unwrap()
     }
 
     fn solve_second(&self, input: &Vec<i64>) -> i64 {
         for noun in 0..=99 {
             for verb in 0..=99 {
-                if run_program(input, noun, verb) == 19690720 {
+                if run_program(input, noun, verb) == Ok(19690720) {
                     return 100 * noun + verb;
                 }
             }
@@ -34,11 +30,11 @@ impl Solver for Problem {
     }
 }
 
-fn run_program(input: &Vec<i64>, noun: i64, verb: i64) -> i64 {
+fn run_program(input: &Vec<i64>, noun: i64, verb: i64) -> Result<i64, IntcodeError> {
     let mut intcode = IntCode::new(input);
-    intcode.memory.store(1, noun);
-    intcode.memory.store(2, verb);
     let mut output = IntInput::new();
-    intcode.advance(&mut output);
-    intcode.memory.read(0)
+    intcode.memory.store(1, noun).unwrap();
+    intcode.memory.store(2, verb).unwrap();
+    intcode.advance(&mut output)?;
+    Ok(intcode.memory.read(0).unwrap())
 }
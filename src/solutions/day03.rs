@@ -1,7 +1,8 @@
+use crate::parse::{fields, parse_lines, parse_number, read_text, ParseError};
 use crate::solutions::day03::Direction::{DOWN, LEFT, RIGHT};
 use crate::solver::Solver;
 use std::collections::HashSet;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 pub struct Problem;
 
@@ -13,12 +14,13 @@ enum Direction {
 }
 
 impl Direction {
-    pub fn from(c: char) -> Direction {
+    pub fn from(c: char) -> Option<Direction> {
         match c {
-            'D' => DOWN,
-            'U' => Direction::UP,
-            'L' => LEFT,
-            _ => RIGHT, // Assuming the input is well formed, or this should return a Result
+            'D' => Some(DOWN),
+            'U' => Some(Direction::UP),
+            'L' => Some(LEFT),
+            'R' => Some(RIGHT),
+            _ => None,
         }
     }
 }
@@ -29,11 +31,16 @@ pub struct Movement {
 }
 
 impl Movement {
-    pub fn from(s: String) -> Movement {
-        Movement {
-            direction: Direction::from(s.chars().nth(0).unwrap()),
-            amount: s[1..].parse().unwrap(),
-        }
+    // `column` is where `s` starts on its line
+    pub fn parse(s: &str, column: usize) -> Result<Movement, ParseError> {
+        let c = s.chars().next();
+        let direction = c.and_then(Direction::from).ok_or_else(|| {
+            ParseError::at(column, format!("expected a direction, found '{}'", s))
+        })?;
+        Ok(Movement {
+            direction,
+            amount: parse_number(&s[1..], column + 1)?,
+        })
     }
 }
 
@@ -42,17 +49,21 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<Vec<Movement>> {
-        let r = BufReader::new(r);
-        r.lines()
-            .map(|x| {
-                x.unwrap()
-                    .split(',')
-                    .map(|x| x.to_string())
-                    .map(|s| Movement::from(s))
-                    .collect()
-            })
-            .collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<Vec<Movement>>, ParseError> {
+        let wires = parse_lines(&read_text(r)?, |l| {
+            fields(l, ',')
+                .into_iter()
+                .map(|(column, s)| Movement::parse(s, column))
+                .collect()
+        })?;
+        if wires.len() != 2 {
+            return Err(ParseError::new(
+                wires.len().min(2) + 1,
+                1,
+                "expected exactly two wires",
+            ));
+        }
+        Ok(wires)
     }
 
     fn solve_first(&self, input: &Vec<Vec<Movement>>) -> i64 {
@@ -1,5 +1,6 @@
+use crate::parse::{parse_separated, read_text, ParseError};
 use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 pub struct Problem;
 
@@ -8,15 +9,12 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> (i64, i64) {
-        let r = BufReader::new(r);
-        let all_input_numbers: Vec<i64> = r
-            .split(b'-')
-            .flatten()
-            .filter_map(|v| String::from_utf8(v).ok())
-            .filter_map(|v| v.to_owned().trim().to_string().parse().ok())
-            .collect();
-        (all_input_numbers[0], all_input_numbers[1])
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<(i64, i64), ParseError> {
+        let all_input_numbers: Vec<i64> = parse_separated(&read_text(r)?, '-')?;
+        match all_input_numbers[..] {
+            [lower, upper] => Ok((lower, upper)),
+            _ => Err(ParseError::at(1, "expected a range such as 123456-654321")),
+        }
     }
 
     fn solve_first(&self, input: &(i64, i64)) -> i64 {
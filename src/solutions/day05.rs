@@ -1,6 +1,7 @@
-use crate::intcode::{IntCode, IntInput};
+use crate::intcode::{read_input, IntCode, IntInput};
+use crate::parse::ParseError;
 use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 pub struct Problem;
 
@@ -9,20 +10,15 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
-        let r = BufReader::new(r);
-        r.split(b',')
-            .flatten()
-            .filter_map(|v| String::from_utf8(v).ok())
-            .filter_map(|v| v.to_owned().trim().to_string().parse().ok())
-            .collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
+        read_input(r)
     }
 
     fn solve_first(&self, input: &Vec<i64>) -> i64 {
         let mut intcode = IntCode::new(input);
         let mut output = IntInput::new();
         intcode.input.push(1i64);
-        intcode.advance(&mut output);
+        intcode.advance(&mut output).unwrap();
         let mut last_output = 0;
         while output.has_input() {
             last_output = output.get();
@@ -34,7 +30,7 @@ impl Solver for Problem {
         let mut intcode = IntCode::new(input);
         let mut output = IntInput::new();
         intcode.input.push(5i64);
-        intcode.advance(&mut output);
+        intcode.advance(&mut output).unwrap();
         let mut last_output = 0;
         while output.has_input() {
             last_output = output.get();
@@ -1,7 +1,7 @@
+use crate::parse::{parse_lines, read_text, ParseError};
 use crate::solver::Solver;
 use std::collections::HashMap;
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::str::FromStr;
 
 pub struct Problem;
@@ -12,11 +12,11 @@ pub struct Orbit {
 }
 
 impl FromStr for Orbit {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.find(")") {
-            None => Err(()),
+            None => Err(ParseError::at(1, format!("expected A)B, found '{}'", s))),
             Some(split_index) => Ok(Orbit {
                 source: s[0..split_index].to_string(),
                 orbital: s[split_index + 1..].trim().to_string(),
@@ -30,12 +30,8 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        BufReader::new(r)
-            .lines()
-            .flatten()
-            .map(|l| l.parse::<Orbit>().expect(""))
-            .collect::<Vec<_>>()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(&read_text(r)?, |l| l.parse::<Orbit>())
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -1,5 +1,7 @@
+use crate::parse::{check_characters, read_text, ParseError};
 use crate::solver::Solver;
-use std::io::{BufReader, Read};
+use crate::{diag, diagln};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -8,10 +10,10 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = String;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        let mut buffer = String::new();
-        BufReader::new(r).read_to_string(&mut buffer).unwrap();
-        buffer.clone()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buffer = read_text(r)?;
+        check_characters(&buffer, "0123456789")?;
+        Ok(buffer)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -57,12 +59,12 @@ fn display(data: &String, width: usize, height: usize) {
     for h in 0..height {
         for w in 0..width {
             if chars[h * width + w] == '1' {
-                print!("*")
+                diag!("*")
             } else {
-                print!(" ")
+                diag!(" ")
             }
         }
-        println!("");
+        diagln!("");
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::intcode::{read_input, IntCode, IntInput};
+use crate::parse::ParseError;
 use crate::solver::Solver;
 use std::io;
 
@@ -9,7 +10,7 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
@@ -18,7 +19,7 @@ impl Solver for Problem {
         int_code.input.push(1);
         let mut output = IntInput::new();
         while !int_code.finished {
-            int_code.advance(&mut output);
+            int_code.advance(&mut output).unwrap();
         }
         int_code.last_output
     }
@@ -28,7 +29,7 @@ impl Solver for Problem {
         int_code.input.push(2);
         let mut output = IntInput::new();
         while !int_code.finished {
-            int_code.advance(&mut output);
+            int_code.advance(&mut output).unwrap();
         }
         int_code.last_output
     }
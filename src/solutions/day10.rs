@@ -1,6 +1,7 @@
 use crate::coords::Coord;
+use crate::parse::{check_characters, read_text, ParseError};
 use crate::solver::Solver;
-use std::io::{BufRead, BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -9,13 +10,10 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        parse_map(
-            &BufReader::new(r)
-                .lines()
-                .map(|l| l.expect("Could not parse line"))
-                .collect(),
-        )
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let text = read_text(r)?;
+        check_characters(&text, ".#")?;
+        Ok(parse_map(&text.lines().map(|l| l.to_string()).collect()))
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -1,6 +1,8 @@
 use crate::intcode::{read_input, IntCode, IntInput};
+use crate::parse::ParseError;
 use crate::solutions::day11::Direction::{DOWN, LEFT, RIGHT, UP};
 use crate::solver::Solver;
+use crate::{diag, diagln};
 use std::collections::HashMap;
 use std::io;
 
@@ -11,7 +13,7 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = String;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
@@ -36,12 +38,12 @@ fn paint(int_code: &mut IntCode, robot: &mut PaintRobot) {
     let mut output = IntInput::new();
     while !int_code.finished {
         int_code.input.push(robot.read() as i64);
-        int_code.advance(&mut output);
+        int_code.advance(&mut output).unwrap();
         robot.paint(output.get() as u8);
         match output.get() {
             0 => robot.rotate_left(),
             1 => robot.rotate_right(),
-            _ => println!("ERROR: Unexpected command"),
+            _ => diagln!("ERROR: Unexpected command"),
         }
         robot.advance();
     }
@@ -144,11 +146,11 @@ impl PaintRobot {
         for y in (min_y..=max_y).rev() {
             for x in min_x..=max_x {
                 match self.read_at(x, y) {
-                    1 => print!("#"),
-                    _ => print!("."),
+                    1 => diag!("#"),
+                    _ => diag!("."),
                 }
             }
-            println!("");
+            diagln!("");
         }
     }
 }
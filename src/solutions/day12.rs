@@ -1,7 +1,8 @@
+use crate::parse::{fields, parse_lines, parse_number, read_text, ParseError};
 use crate::solver::Solver;
 use num::integer::Integer;
 use permutator::Combination;
-use std::io::{BufRead, BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -10,12 +11,8 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        BufReader::new(r)
-            .lines()
-            .map(|l| l.unwrap())
-            .map(|l| Moon::from_string(l))
-            .collect::<Vec<Moon>>()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(&read_text(r)?, |l| Moon::from_string(l.to_string()))
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -83,28 +80,34 @@ pub struct Moon {
 }
 
 impl Moon {
-    pub fn from_string(data: String) -> Moon {
-        let parts = data[1..data.len() - 1].split(",").collect::<Vec<&str>>();
-        let x = parts[0].split('=').collect::<Vec<&str>>()[1]
-            .trim()
-            .parse::<i64>()
-            .unwrap();
-        let y = parts[1].split('=').collect::<Vec<&str>>()[1]
-            .trim()
-            .parse::<i64>()
-            .unwrap();
-        let z = parts[2].split('=').collect::<Vec<&str>>()[1]
-            .trim()
-            .parse::<i64>()
-            .unwrap();
-        Moon {
-            x: x,
-            y: y,
-            z: z,
+    pub fn from_string(data: String) -> Result<Moon, ParseError> {
+        let data = data.trim_end();
+        let expected =
+            || ParseError::at(1, format!("expected <x=.., y=.., z=..>, found '{}'", data));
+        let inner = data
+            .strip_prefix('<')
+            .and_then(|d| d.strip_suffix('>'))
+            .ok_or_else(expected)?;
+        let parts = fields(inner, ',');
+        if parts.len() != 3 {
+            return Err(expected());
+        }
+        let mut values = Vec::new();
+        for ((column, part), name) in parts.into_iter().zip(&["x=", "y=", "z="]) {
+            // Columns in `inner` are one short because of the '<'
+            let value = part.strip_prefix(name).ok_or_else(|| {
+                ParseError::at(column + 1, format!("expected {}.., found '{}'", name, part))
+            })?;
+            values.push(parse_number(value.trim(), column + 3)?);
+        }
+        Ok(Moon {
+            x: values[0],
+            y: values[1],
+            z: values[2],
             vx: 0,
             vy: 0,
             vz: 0,
-        }
+        })
     }
 
     pub fn energy(&self) -> u64 {
@@ -148,7 +151,7 @@ impl Moon {
 
 #[test]
 fn test_from_string() {
-    let m = Moon::from_string("<x=19, y=-10, z=-7>".to_string());
+    let m = Moon::from_string("<x=19, y=-10, z=-7>".to_string()).unwrap();
     assert!(m.x == 19);
     assert!(m.y == -10);
     assert!(m.z == -7);
@@ -156,3 +159,16 @@ fn test_from_string() {
     assert!(m.vy == 0);
     assert!(m.vz == 0);
 }
+
+#[test]
+fn test_from_string_errors() {
+    let e = Moon::from_string("<x=19, y=-1o, z=-7>".to_string())
+        .err()
+        .unwrap();
+    assert_eq!((e.line, e.column), (1, 10));
+    let e = Moon::from_string("<x=19, w=1, z=-7>".to_string())
+        .err()
+        .unwrap();
+    assert_eq!((e.line, e.column), (1, 8));
+    assert!(Moon::from_string("<x=19, y=1>".to_string()).is_err());
+}
@@ -1,4 +1,5 @@
-use crate::intcode::{read_input, IntCode, IntInput};
+use crate::intcode::{read_input, IntCode, IntInput, MemoryBackend};
+use crate::parse::ParseError;
 use crate::solver::Solver;
 use std::collections::HashMap;
 use std::io;
@@ -10,7 +11,7 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
@@ -19,7 +20,7 @@ impl Solver for Problem {
         let mut output = IntInput::new();
         let mut block_tiles = 0;
         while !int_code.finished {
-            int_code.advance(&mut output);
+            int_code.advance(&mut output).unwrap();
             while output.has_input() {
                 output.get();
                 output.get();
@@ -35,10 +36,10 @@ impl Solver for Problem {
         let mut int_code = IntCode::new(input);
         let mut game_output = IntInput::new();
         let mut game = ArcadeGame::new();
-        int_code.memory.store(0, 2);
+        int_code.memory.store(0, 2).unwrap();
 
         loop {
-            int_code.advance(&mut game_output);
+            int_code.advance(&mut game_output).unwrap();
             game.process_input(&mut game_output);
             int_code.input.push(game.get_paddle_move());
             if game.screen.block_count() == 0 {
@@ -1,7 +1,8 @@
+use crate::parse::{column, fields, parse_lines, parse_number, read_text, ParseError};
 use crate::solver::Solver;
 use std::cmp::min;
 use std::collections::{HashMap, VecDeque};
-use std::io::{BufRead, BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -10,12 +11,8 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        BufReader::new(r)
-            .lines()
-            .map(|l| l.unwrap())
-            .map(|l| Reaction::from_string(l))
-            .collect::<Vec<Reaction>>()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        parse_lines(&read_text(r)?, |l| Reaction::from_string(l.to_string()))
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -139,11 +136,17 @@ pub struct Ingredient {
 }
 
 impl Ingredient {
-    pub fn from_string(data: String) -> Ingredient {
-        let parts = data.split(" ").collect::<Vec<&str>>();
-        Ingredient {
-            name: parts[1].trim().to_string(),
-            count: parts[0].trim().parse::<u64>().unwrap(),
+    // `column` is where `data` starts on its line
+    pub fn from_string(data: &str, column: usize) -> Result<Ingredient, ParseError> {
+        match data.split(" ").collect::<Vec<&str>>()[..] {
+            [count, name] if !name.is_empty() => Ok(Ingredient {
+                name: name.trim().to_string(),
+                count: parse_number(count, column)?,
+            }),
+            _ => Err(ParseError::at(
+                column,
+                format!("expected a quantity and a chemical, found '{}'", data),
+            )),
         }
     }
 }
@@ -154,23 +157,27 @@ pub struct Reaction {
 }
 
 impl Reaction {
-    pub fn from_string(data: String) -> Reaction {
-        let parts = data.split("=>").collect::<Vec<&str>>();
-        Reaction {
-            output: Ingredient::from_string(parts[1].trim().to_string()),
-            ingredients: parts[0]
-                .split(",")
-                .map(|s| Ingredient::from_string(s.trim().to_string()))
-                .collect::<Vec<Ingredient>>(),
-        }
+    pub fn from_string(data: String) -> Result<Reaction, ParseError> {
+        let arrow = data
+            .find("=>")
+            .ok_or_else(|| ParseError::at(1, format!("expected '=>' in '{}'", data)))?;
+        let output = &data[arrow + 2..];
+        let output_start = arrow + 2 + output.len() - output.trim_start().len();
+        Ok(Reaction {
+            output: Ingredient::from_string(output.trim(), column(&data, output_start))?,
+            ingredients: fields(&data[..arrow], ',')
+                .into_iter()
+                .map(|(column, s)| Ingredient::from_string(s, column))
+                .collect::<Result<Vec<Ingredient>, ParseError>>()?,
+        })
     }
 }
 
 #[test]
 fn test_produce() {
     let mut reactions = Vec::new();
-    reactions.push(Reaction::from_string("10 ORE => 3 TEST".to_string()));
-    reactions.push(Reaction::from_string("2 TEST => 4 FUEL".to_string()));
+    reactions.push(Reaction::from_string("10 ORE => 3 TEST".to_string()).unwrap());
+    reactions.push(Reaction::from_string("2 TEST => 4 FUEL".to_string()).unwrap());
     let mut factory = Factory::new(&reactions);
     assert!(factory.produce_one("TEST".to_string()) == 10);
     assert!(factory.produce_one("FUEL".to_string()) == 0);
@@ -178,15 +185,31 @@ fn test_produce() {
 
 #[test]
 fn ingredient_from_string() {
-    let i1 = Ingredient::from_string("10 ORE".to_string());
+    let i1 = Ingredient::from_string("10 ORE", 1).unwrap();
     assert!(i1.count == 10);
     assert!(i1.name == "ORE".to_string());
 }
 
 #[test]
 fn reaction_from_string() {
-    let r1 = Reaction::from_string("5 GHVJ, 1 RGKB, 1 GCTBC => 6 HKMV".to_string());
+    let r1 = Reaction::from_string("5 GHVJ, 1 RGKB, 1 GCTBC => 6 HKMV".to_string()).unwrap();
     assert!(r1.output.count == 6);
     assert!(r1.output.name == "HKMV".to_string());
     assert!(r1.ingredients.len() == 3);
 }
+
+#[test]
+fn reaction_from_string_errors() {
+    let e = Reaction::from_string("5 GHVJ, x RGKB => 6 HKMV".to_string())
+        .err()
+        .unwrap();
+    assert_eq!(
+        (e.column, e.message.as_str()),
+        (9, "expected a number, found 'x'")
+    );
+    let e = Reaction::from_string("5 GHVJ => HKMV".to_string())
+        .err()
+        .unwrap();
+    assert_eq!(e.column, 11);
+    assert!(Reaction::from_string("5 GHVJ, 6 HKMV".to_string()).is_err());
+}
@@ -1,7 +1,9 @@
 use crate::coords::{Coord, Direction};
-use crate::intcode::{read_input, IntCode, IntInput};
+use crate::diagln;
+use crate::intcode::{read_input, IntCode};
+use crate::parse::ParseError;
 use crate::solver::Solver;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, VecDeque};
 use std::io;
 
 pub struct Problem;
@@ -11,21 +13,21 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut intcode = IntCode::new(input);
+        let intcode = IntCode::new(input);
         let mut repair_drone = RepairBot::new();
-        repair_drone.explore(&mut intcode);
+        repair_drone.explore(&intcode);
         repair_drone.find_oxygen_distance()
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut intcode = IntCode::new(input);
+        let intcode = IntCode::new(input);
         let mut repair_drone = RepairBot::new();
-        repair_drone.explore(&mut intcode);
+        repair_drone.explore(&intcode);
         repair_drone.diffuse()
     }
 }
@@ -74,13 +76,6 @@ impl RepairBot {
         }
     }
 
-    fn is_a_wall(&self, cell: &Coord) -> bool {
-        if self.cells.contains_key(cell) && *self.cells.get(cell).unwrap() == Cell::WALL {
-            return true;
-        }
-        false
-    }
-
     fn is_empty_cell(&self, cell: &Coord) -> bool {
         if self.cells.contains_key(cell) && *self.cells.get(cell).unwrap() == Cell::EMPTY {
             return true;
@@ -121,38 +116,36 @@ impl RepairBot {
 
         turn
     }
-    fn explore(&mut self, intcode: &mut IntCode) {
+    // Flood fill from the origin, every reached cell keeps a copy of the droid's computer
+    // so we can branch from it instead of walking the droid back and forth
+    fn explore(&mut self, intcode: &IntCode) {
         let mut to_visit = VecDeque::new();
-        let mut visited = HashSet::new();
-        visited.insert(Coord { x: 0, y: 0 });
-        for d in Direction::all() {
-            to_visit.push_back(self.position.next(&d));
-        }
+        to_visit.push_back((self.position, intcode.clone()));
 
-        while !to_visit.is_empty() {
-            let cell_to_visit = to_visit.pop_back().unwrap();
-
-            // In case we found already it's a wall
-            if self.is_a_wall(&cell_to_visit) {
-                continue;
-            }
-
-            self.move_to(cell_to_visit, intcode);
-
-            visited.insert(self.position);
-            for d in Direction::all() {
-                let nearby_cell = self.position.next(&d);
-                if self.is_a_wall(&nearby_cell) {
+        while let Some((cell, droid)) = to_visit.pop_front() {
+            for direction in Direction::all() {
+                let next_cell = cell.next(&direction);
+                if self.cells.contains_key(&next_cell) {
                     continue;
                 }
-                if !visited.contains(&nearby_cell) {
-                    to_visit.push_back(nearby_cell);
+                let mut next_droid = droid.clone();
+                next_droid.input.push(direction.value());
+                match next_droid.run_for_outputs(1).unwrap()[..] {
+                    [0] => {
+                        self.cells.insert(next_cell, Cell::WALL);
+                    }
+                    [1] => {
+                        self.cells.insert(next_cell, Cell::EMPTY);
+                        to_visit.push_back((next_cell, next_droid));
+                    }
+                    [2] => {
+                        self.cells.insert(next_cell, Cell::OXYGEN);
+                        self.oxygen = Some(next_cell);
+                        to_visit.push_back((next_cell, next_droid));
+                    }
+                    ref r => diagln!("ERROR: Unexpcted answer: {:?}", r),
                 }
             }
-
-            if *self.cells.get(&self.position).unwrap() == Cell::OXYGEN {
-                self.oxygen = Some(self.position);
-            }
         }
     }
 
@@ -160,33 +153,6 @@ impl RepairBot {
         self.minimum_distance(&self.oxygen.unwrap(), &Coord { x: 0, y: 0 })
     }
 
-    fn move_to(&mut self, target: Coord, intcode: &mut IntCode) {
-        let mut output = IntInput::new();
-        for direction in self.plot_move_to(target).steps {
-            intcode.input.push(direction.value());
-            intcode.advance(&mut output);
-            let next_cell = self.position.next(&direction);
-            match output.get() {
-                0 => {
-                    self.cells.insert(next_cell, Cell::WALL);
-                }
-                1 => {
-                    self.position = next_cell;
-                    self.cells.insert(next_cell, Cell::EMPTY);
-                }
-                2 => {
-                    self.position = next_cell;
-                    self.cells.insert(next_cell, Cell::OXYGEN);
-                }
-                r => println!("ERROR: Unexpcted answer: {}", r),
-            }
-        }
-    }
-
-    fn plot_move_to(&self, target: Coord) -> Path {
-        self.find_best_path(&self.position, &target)
-    }
-
     fn minimum_distance(&self, source: &Coord, destination: &Coord) -> u64 {
         self.find_best_path(source, destination).len() as u64
     }
@@ -255,6 +221,51 @@ fn test_best_path() {
     assert!(repair_bot.minimum_distance(&Coord { x: 0, y: 0 }, &Coord { x: 2, y: 0 }) == 4);
 }
 
+#[test]
+fn test_explore() {
+    use crate::intcode::asm::assemble;
+
+    // A corridor running east from the origin, with the oxygen system two cells away
+    let program = assemble(
+        "
+        loop:   IN [dir]
+                EQ [dir], #3, [tmp]
+                JNZ [tmp], #west
+                EQ [dir], #4, [tmp]
+                JNZ [tmp], #east
+                OUT #0
+                JZ #0, #loop
+        west:   ADD [pos], #-1, [next]
+                JZ #0, #move
+        east:   ADD [pos], #1, [next]
+        move:   LT [next], #0, [tmp]
+                JNZ [tmp], #wall
+                LT #2, [next], [tmp]
+                JNZ [tmp], #wall
+                ADD [next], #0, [pos]
+                EQ [pos], #2, [tmp]
+                JNZ [tmp], #oxygen
+                OUT #1
+                JZ #0, #loop
+        oxygen: OUT #2
+                JZ #0, #loop
+        wall:   OUT #0
+                JZ #0, #loop
+        dir:    DATA 0
+        tmp:    DATA 0
+        pos:    DATA 0
+        next:   DATA 0
+        ",
+    )
+    .unwrap();
+    let mut repair_bot = RepairBot::new();
+    repair_bot.explore(&IntCode::new(&program));
+    assert!(repair_bot.oxygen == Some(Coord { x: 2, y: 0 }));
+    assert!(repair_bot.cells.len() == 11);
+    assert!(repair_bot.find_oxygen_distance() == 2);
+    assert!(repair_bot.diffuse() == 2);
+}
+
 #[derive(PartialEq)]
 enum Cell {
     EMPTY,
@@ -1,5 +1,6 @@
+use crate::parse::{check_characters, read_text, ParseError};
 use crate::solver::Solver;
-use std::io::{BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -8,10 +9,10 @@ impl Solver for Problem {
     type Output1 = String;
     type Output2 = String;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        let mut buffer = String::new();
-        BufReader::new(r).read_to_string(&mut buffer).expect("");
-        buffer.trim().to_string()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buffer = read_text(r)?;
+        check_characters(&buffer, "0123456789")?;
+        Ok(buffer.trim().to_string())
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -1,5 +1,8 @@
 use crate::coords::{Coord, Direction};
-use crate::intcode::{read_input, IntCode, IntInput};
+use crate::diagln;
+use crate::intcode::ascii::AsciiTerminal;
+use crate::intcode::{read_input, IntCode, MemoryBackend};
+use crate::parse::ParseError;
 use crate::solver::Solver;
 use core::fmt;
 use std::collections::HashMap;
@@ -12,23 +15,23 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut intcode = IntCode::new(input);
-        let camera = Camera::create(&mut intcode);
+        let camera = Camera::create(IntCode::new(input));
         camera.aligment_sum()
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut intcode = IntCode::new(input);
-        let camera = Camera::create(&mut intcode);
+        let camera = Camera::create(IntCode::new(input));
         let (routine, a, b, c) = camera.draw_path();
 
-        let mut intcode = IntCode::new(input);
-        camera.send_path(&mut intcode, routine, a, b, c)
+        match camera.send_path(IntCode::new(input), routine, a, b, c) {
+            Ok(dust) => dust,
+            Err(e) => panic!("{}", e),
+        }
     }
 }
 
@@ -66,40 +69,19 @@ impl fmt::Debug for Camera {
 impl Camera {
     fn send_path(
         &self,
-        intcode: &mut IntCode,
+        mut intcode: IntCode,
         routine: String,
         a: String,
         b: String,
         c: String,
-    ) -> i64 {
-        intcode.memory.store(0, 2);
-        for c in routine.chars() {
-            intcode.input.push(c as i64);
-        }
-        intcode.input.push(10);
-        for c in a.chars() {
-            intcode.input.push(c as i64);
-        }
-        intcode.input.push(10);
-        for c in b.chars() {
-            intcode.input.push(c as i64);
-        }
-        intcode.input.push(10);
-        for c in c.chars() {
-            intcode.input.push(c as i64);
-        }
-        intcode.input.push(10);
-        intcode.input.push('n' as i64);
-        intcode.input.push(10);
-
-        let mut output = IntInput::new();
-        intcode.advance(&mut output);
-
-        let mut return_code = 0;
-        while output.has_input() {
-            return_code = output.get();
-        }
-        return_code
+    ) -> Result<i64, String> {
+        intcode.memory.store(0, 2).map_err(|e| e.to_string())?;
+        let mut terminal = AsciiTerminal::new(intcode);
+        terminal.send_lines(&[&routine, &a, &b, &c, "n"]);
+        let output = terminal.read_until_blocked().map_err(|e| e.to_string())?;
+        output
+            .result()
+            .ok_or_else(|| format!("The robot didn't report any dust:\n{}", output.text))
     }
 
     fn draw_path(&self) -> (String, String, String, String) {
@@ -154,10 +136,10 @@ impl Camera {
         *self.cells.get(coord).unwrap() == Cell::SCAFFOLD
     }
 
-    pub fn create(intcode: &mut IntCode) -> Camera {
+    pub fn create(intcode: IntCode) -> Camera {
         let mut cells = HashMap::new();
-        let mut output = IntInput::new();
-        intcode.advance(&mut output);
+        let mut terminal = AsciiTerminal::new(intcode);
+        let view = terminal.read_until_blocked().unwrap().text;
 
         let mut x = 0;
         let mut y = 0;
@@ -165,28 +147,27 @@ impl Camera {
         let mut robot_x = 0;
         let mut robot_y = 0;
 
-        while output.has_input() {
-            let view = output.get();
-            match view {
-                35 => {
+        for c in view.chars() {
+            match c {
+                '#' => {
                     cells.insert(Coord { x: x, y: y }, Cell::SCAFFOLD);
                     x += 1;
                 }
-                94 => {
+                '^' => {
                     robot_x = x;
                     robot_y = y;
                     cells.insert(Coord { x: x, y: y }, Cell::SCAFFOLD);
                     x += 1;
                 }
-                46 => {
+                '.' => {
                     cells.insert(Coord { x: x, y: y }, Cell::EMPTY);
                     x += 1
                 }
-                10 => {
+                '\n' => {
                     x = 0;
                     y += 1;
                 }
-                _ => println!("ERROR: Unexpected output: {}", view),
+                _ => diagln!("ERROR: Unexpected output: {}", c),
             }
         }
         Camera {
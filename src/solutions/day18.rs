@@ -1,8 +1,10 @@
 use crate::coords::{Coord, Direction};
+use crate::diagln;
+use crate::parse::{check_characters, read_text, ParseError};
 use crate::solver::Solver;
 use core::fmt;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::io::{BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -11,10 +13,14 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        let mut buffer = String::new();
-        BufReader::new(r).read_to_string(&mut buffer).expect("");
-        buffer
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buffer = read_text(r)?;
+        // Walls, open passages, the entrances, keys and doors
+        check_characters(
+            &buffer,
+            "#.@abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ",
+        )?;
+        Ok(buffer)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -253,7 +259,7 @@ impl MultiMaze {
                         doors.insert(position, c.to_ascii_lowercase());
                     }
                     _ => {
-                        println!("ERROR: Unexptected value on the map: {}", c);
+                        diagln!("ERROR: Unexptected value on the map: {}", c);
                     }
                 }
             }
@@ -475,7 +481,7 @@ impl Maze {
                         doors.insert(position, c.to_ascii_lowercase());
                     }
                     _ => {
-                        println!("ERROR: Unexptected value on the map: {}", c);
+                        diagln!("ERROR: Unexptected value on the map: {}", c);
                     }
                 }
             }
@@ -1,5 +1,7 @@
 use crate::coords::Coord;
-use crate::intcode::{read_input, IntCode, IntInput};
+use crate::intcode::compiled::CompiledIntCode;
+use crate::intcode::read_input;
+use crate::parse::ParseError;
 use crate::solver::Solver;
 use std::collections::HashMap;
 use std::io;
@@ -11,7 +13,7 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
@@ -24,8 +26,8 @@ impl Solver for Problem {
     }
 }
 
-fn find_starting_point(program: &Vec<i64>, width: i64, height: i64) -> u64 {
-    let mut beam_map = BeamMap::new();
+fn find_starting_point(program: &[i64], width: i64, height: i64) -> u64 {
+    let mut beam_map = BeamMap::new(program);
 
     let maximum_distance = width * height;
     // The starting number has been obtained by looking at the map, but could be obtained programmatically
@@ -36,7 +38,7 @@ fn find_starting_point(program: &Vec<i64>, width: i64, height: i64) -> u64 {
                 if x + y != total_distance {
                     continue;
                 }
-                if verify(program, &mut beam_map, x, y, width, height) {
+                if verify(&mut beam_map, x, y, width, height) {
                     return (x * 10000 + y) as u64;
                 }
             }
@@ -47,41 +49,34 @@ fn find_starting_point(program: &Vec<i64>, width: i64, height: i64) -> u64 {
 
 struct BeamMap {
     points: HashMap<Coord, bool>,
+    drone: CompiledIntCode,
 }
 
 impl BeamMap {
-    pub fn new() -> BeamMap {
+    pub fn new(program: &[i64]) -> BeamMap {
         BeamMap {
             points: HashMap::new(),
+            drone: CompiledIntCode::new(program),
         }
     }
-    fn get(&mut self, program: &Vec<i64>, x: i64, y: i64) -> bool {
+    fn get(&mut self, x: i64, y: i64) -> bool {
         let coord = Coord { x: x, y: y };
         if self.points.contains_key(&coord) {
             return *self.points.get(&coord).unwrap();
         }
-        let mut output = IntInput::new();
-        let mut intcode = IntCode::new(program);
-        intcode.input.push(x);
-        intcode.input.push(y);
-        intcode.advance(&mut output);
-        let result = output.get() == 1;
+        let mut intcode = self.drone.clone();
+        intcode.input().push(x);
+        intcode.input().push(y);
+        let result = intcode.run_until_blocked().unwrap().0[0] == 1;
         self.points.insert(coord, result);
         result
     }
 }
 
-fn verify(
-    program: &Vec<i64>,
-    beam_map: &mut BeamMap,
-    startx: i64,
-    starty: i64,
-    width: i64,
-    height: i64,
-) -> bool {
+fn verify(beam_map: &mut BeamMap, startx: i64, starty: i64, width: i64, height: i64) -> bool {
     for y in starty..starty + height {
         for x in startx..startx + width {
-            let result = beam_map.get(program, x, y);
+            let result = beam_map.get(x, y);
             if !result {
                 return false;
             }
@@ -90,17 +85,16 @@ fn verify(
     true
 }
 
-fn count_tractor(program: &Vec<i64>, width: usize, height: usize) -> u64 {
+fn count_tractor(program: &[i64], width: usize, height: usize) -> u64 {
     let mut affected = 0;
+    let drone = CompiledIntCode::new(program);
 
     for y in 0..height {
         for x in 0..width {
-            let mut intcode = IntCode::new(program);
-            let mut output = IntInput::new();
-            intcode.input.push(x as i64);
-            intcode.input.push(y as i64);
-            intcode.advance(&mut output);
-            let result = output.get();
+            let mut intcode = drone.clone();
+            intcode.input().push(x as i64);
+            intcode.input().push(y as i64);
+            let result = intcode.run_until_blocked().unwrap().0[0];
             if result == 1 {
                 affected += 1;
             //print!("#");
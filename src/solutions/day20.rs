@@ -1,7 +1,8 @@
 use crate::coords::{Coord, Direction};
+use crate::parse::{check_characters, read_text, ParseError};
 use crate::solver::Solver;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::io::{BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -10,10 +11,10 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        let mut buffer = String::new();
-        BufReader::new(r).read_to_string(&mut buffer).expect("");
-        buffer
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buffer = read_text(r)?;
+        check_characters(&buffer, " #.ABCDEFGHIJKLMNOPQRSTUVWXYZ")?;
+        Ok(buffer)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
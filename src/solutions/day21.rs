@@ -1,6 +1,8 @@
+use crate::parse::ParseError;
 use crate::solver::Solver;
 use std::io;
-use crate::intcode::{read_input, IntCode, IntInput};
+use crate::intcode::ascii::AsciiTerminal;
+use crate::intcode::{read_input, IntCode};
 
 pub struct Problem;
 
@@ -9,50 +11,33 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut intcode = IntCode::new(input);
-        let mut output = IntInput::new();
-        send_string(&mut intcode, "OR A J".to_string());
-        send_string(&mut intcode, "AND C J".to_string());
-        send_string(&mut intcode, "NOT J J".to_string());
-        send_string(&mut intcode, "AND D J".to_string());
-        send_string(&mut intcode, "WALK".to_string());
-        intcode.advance(&mut output);
-        let mut last_code = 0;
-        while output.has_input() {
-            last_code = output.get();
-        }
-        last_code
+        run_springscript(input, &["OR A J", "AND C J", "NOT J J", "AND D J", "WALK"])
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut intcode = IntCode::new(input);
-        let mut output = IntInput::new();
-        send_string(&mut intcode, "OR A J".to_string());
-        send_string(&mut intcode, "AND B J".to_string());
-        send_string(&mut intcode, "AND C J".to_string());
-        send_string(&mut intcode, "NOT J J".to_string());
-        send_string(&mut intcode, "AND D J".to_string());
-        send_string(&mut intcode, "OR E T".to_string());
-        send_string(&mut intcode, "OR H T".to_string());
-        send_string(&mut intcode, "AND T J".to_string());
-        send_string(&mut intcode, "RUN".to_string());
-        intcode.advance(&mut output);
-        let mut last_code = 0;
-        while output.has_input() {
-            last_code = output.get();
-        }
-        last_code
+        run_springscript(
+            input,
+            &[
+                "OR A J", "AND B J", "AND C J", "NOT J J", "AND D J", "OR E T", "OR H T", "AND T J",
+                "RUN",
+            ],
+        )
     }
 }
 
-fn send_string(intcode: &mut IntCode, data: String ) {
-    for c in data.chars()  {
-        intcode.input.push(c as i64);
+// Returns the hull damage reported once the droid makes it across
+fn run_springscript(program: &[i64], script: &[&str]) -> i64 {
+    let intcode: IntCode = IntCode::with_backend(program);
+    let mut terminal = AsciiTerminal::new(intcode);
+    terminal.send_lines(script);
+    let output = terminal.read_until_blocked().unwrap();
+    match output.result() {
+        Some(damage) => damage,
+        None => panic!("The droid didn't make it:\n{}", output.text),
     }
-    intcode.input.push(10);
 }
@@ -1,22 +1,21 @@
+use crate::parse::{column, parse_lines, parse_number, read_text, ParseError};
 use crate::solver::Solver;
 use mod_exp::mod_exp;
-use std::io::{BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
 impl Solver for Problem {
-    type Input = String;
+    type Input = Vec<Shuffle>;
     type Output1 = u64;
     type Output2 = i128;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        let mut buffer = String::new();
-        BufReader::new(r).read_to_string(&mut buffer).expect("");
-        buffer
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        Shuffle::from_multiple_strings(&read_text(r)?)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let operations = Shuffle::from_multiple_strings(input);
+        let operations = input;
         let mut position = 2019;
         for op in operations {
             position = op.move_position(position, 10007);
@@ -25,7 +24,7 @@ impl Solver for Problem {
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let operations = Shuffle::from_multiple_strings(input);
+        let operations = input;
         let position = 2020;
         let deck_size = 119315717514047i128;
         let iterations = 101741582076661i128;
@@ -52,14 +51,14 @@ impl Solver for Problem {
 }
 
 #[derive(PartialEq, Clone)]
-enum Operation {
+pub enum Operation {
     NewStack,
     Cut,
     Increment,
 }
 
 #[derive(Clone)]
-struct Shuffle {
+pub struct Shuffle {
     operation: Operation,
     value: i64,
 }
@@ -76,32 +75,33 @@ impl Shuffle {
         }
     }
 
-    pub fn from_string(data: String) -> Shuffle {
+    pub fn from_string(data: String) -> Result<Shuffle, ParseError> {
+        let data = data.trim_end();
         if data.contains("deal into new stack") {
-            return Shuffle {
+            return Ok(Shuffle {
                 operation: Operation::NewStack,
                 value: 0,
-            };
-        }
-        let value = data.split(' ').next_back().unwrap().parse::<i64>().unwrap();
-        if data.contains("cut") {
-            return Shuffle {
-                operation: Operation::Cut,
-                value: value,
-            };
-        }
-        Shuffle {
-            operation: Operation::Increment,
-            value: value,
+            });
         }
+        let operation = if data.starts_with("cut ") {
+            Operation::Cut
+        } else if data.starts_with("deal with increment ") {
+            Operation::Increment
+        } else {
+            return Err(ParseError::at(
+                1,
+                format!("unknown shuffle technique '{}'", data),
+            ));
+        };
+        let start = data.rfind(' ').unwrap() + 1;
+        Ok(Shuffle {
+            operation,
+            value: parse_number(&data[start..], column(data, start))?,
+        })
     }
 
-    pub fn from_multiple_strings(data: &String) -> Vec<Shuffle> {
-        data.trim()
-            .to_string()
-            .split('\n')
-            .map(|l| Shuffle::from_string(l.trim().to_string()))
-            .collect::<Vec<Shuffle>>()
+    pub fn from_multiple_strings(data: &str) -> Result<Vec<Shuffle>, ParseError> {
+        parse_lines(data, |l| Shuffle::from_string(l.to_string()))
     }
 
     fn move_position(&self, position: u64, deck_size: u64) -> u64 {
@@ -136,36 +136,51 @@ deal into new stack
 deal into new stack
 "
     .to_string();
-    let v = Shuffle::from_multiple_strings(&s);
+    let v = Shuffle::from_multiple_strings(&s).unwrap();
     assert!(v.len() == 3);
 }
 
 #[test]
 fn test_move_position() {
-    let s = Shuffle::from_string("deal into new stack".to_string());
+    let s = Shuffle::from_string("deal into new stack".to_string()).unwrap();
     assert!(s.move_position(0, 10) == 9);
     assert!(s.move_position(4, 10) == 5);
-    let s = Shuffle::from_string("deal with increment 3".to_string());
+    let s = Shuffle::from_string("deal with increment 3".to_string()).unwrap();
     assert!(s.move_position(0, 10) == 0);
     assert!(s.move_position(1, 10) == 3);
     assert!(s.move_position(7, 10) == 1);
     assert!(s.move_position(9, 10) == 7);
-    let s = Shuffle::from_string("cut 3".to_string());
+    let s = Shuffle::from_string("cut 3".to_string()).unwrap();
     assert!(s.move_position(0, 10) == 7);
     assert!(s.move_position(3, 10) == 0);
-    let s = Shuffle::from_string("cut -4".to_string());
+    let s = Shuffle::from_string("cut -4".to_string()).unwrap();
     assert!(s.move_position(0, 10) == 4);
     assert!(s.move_position(6, 10) == 0);
 }
 
 #[test]
 fn test_shuffle_from_string() {
-    let s = Shuffle::from_string("deal into new stack".to_string());
+    let s = Shuffle::from_string("deal into new stack".to_string()).unwrap();
     assert!(s.operation == Operation::NewStack);
-    let s = Shuffle::from_string("deal with increment 29".to_string());
+    let s = Shuffle::from_string("deal with increment 29".to_string()).unwrap();
     assert!(s.operation == Operation::Increment);
     assert!(s.value == 29);
-    let s = Shuffle::from_string("cut -4398".to_string());
+    let s = Shuffle::from_string("cut -4398".to_string()).unwrap();
     assert!(s.operation == Operation::Cut);
     assert!(s.value == -4398);
 }
+
+#[test]
+fn test_shuffle_from_string_errors() {
+    let e = Shuffle::from_multiple_strings("cut 3\ndeal with increment x\n")
+        .err()
+        .unwrap();
+    assert_eq!((e.line, e.column), (2, 21));
+    let e = Shuffle::from_multiple_strings("cut 3\nshuffle\n")
+        .err()
+        .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "line 2, column 1: unknown shuffle technique 'shuffle'"
+    );
+}
@@ -1,6 +1,7 @@
-use crate::intcode::{read_input, IntCode, IntInput};
+use crate::intcode::network::{Control, Nat, Network, Packet};
+use crate::intcode::read_input;
+use crate::parse::ParseError;
 use crate::solver::Solver;
-use std::collections::HashMap;
 use std::io;
 
 pub struct Problem;
@@ -10,165 +11,63 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let num_computers = 50;
-        let mut computers = Vec::new();
-        for i in 0..num_computers {
-            computers.push(IntCode::new(input));
-            computers[i].input.set_nonblocking();
-            computers[i].input.push(i as i64);
-        }
-
-        let mut last_y = 0;
-
-        let mut packets = PacketOutput::new();
-        loop {
-            let mut should_stop = false;
-            let mut output = IntInput::new();
-            for i in 0..num_computers {
-                computers[i].step(&mut output);
-                while output.has_input() {
-                    let val = output.get();
-                    let (destination, x, y) = packets.push_packet_piece(i as i64, val);
-                    if destination == 255 {
-                        should_stop = true;
-                        last_y = y;
-                        break;
-                    }
-                    if destination < 0 {
-                        continue;
-                    }
-                    computers[destination as usize].input.push(x);
-                    computers[destination as usize].input.push(y);
-                }
-            }
-
-            if should_stop {
-                break;
-            }
-        }
-
-        last_y
+        let mut nat = FirstPacket { y: 0 };
+        Network::new(input, 50).run_threaded(&mut nat).unwrap();
+        nat.y
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let num_computers = 50;
-        let mut computers = Vec::new();
-        for i in 0..num_computers {
-            computers.push(IntCode::new(input));
-            computers[i].input.set_nonblocking();
-            computers[i].input.push(i as i64);
-        }
-
-        let mut nat_x = 0;
-        let mut nat_y = 0;
-        let mut steps_since_output = 0;
-
-        let mut prev_y_sent = -1;
-
-        let mut packets = PacketOutput::new();
-        loop {
-            let mut should_stop = false;
-            let mut output = IntInput::new();
-            steps_since_output += 1;
-            for i in 0..num_computers {
-                computers[i].step(&mut output);
-                while output.has_input() {
-                    steps_since_output = 0;
-                    let val = output.get();
-                    let (destination, x, y) = packets.push_packet_piece(i as i64, val);
-                    if destination == 255 {
-                        nat_x = x;
-                        nat_y = y;
-                        break;
-                    }
-                    if destination < 0 {
-                        continue;
-                    }
-                    computers[destination as usize].input.push(x);
-                    computers[destination as usize].input.push(y);
-                }
-            }
-
-            if steps_since_output > 1000 {
-                if prev_y_sent == nat_y {
-                    should_stop = true;
-                }
-                steps_since_output = 0;
-                computers[0].input.push(nat_x);
-                computers[0].input.push(nat_y);
-                prev_y_sent = nat_y;
-            }
-
-            if should_stop {
-                break;
-            }
-        }
-
-        prev_y_sent
+        let mut nat = RepeatedWakeUp {
+            last: None,
+            prev_y_sent: None,
+        };
+        Network::new(input, 50).run_threaded(&mut nat).unwrap();
+        nat.prev_y_sent.unwrap()
     }
 }
 
-struct PartialPacket {
-    stage: u8,
-    destination: i64,
-    x: i64,
+// Stops at the first packet sent to address 255
+struct FirstPacket {
     y: i64,
 }
 
-impl PartialPacket {
-    pub fn new(destination: i64) -> PartialPacket {
-        PartialPacket {
-            stage: 1,
-            destination: destination,
-            x: 0,
-            y: 0,
-        }
+impl Nat for FirstPacket {
+    fn receive(&mut self, packet: Packet) -> Control {
+        self.y = packet.y;
+        Control::Stop
     }
-    fn add_value(&mut self, value: i64) {
-        match self.stage {
-            1 => {
-                self.x = value;
-            }
-            2 => {
-                self.y = value;
-            }
-            _ => unreachable!(),
-        }
-        self.stage += 1;
-    }
-    fn is_complete(&self) -> bool {
-        self.stage == 3
+
+    fn wake(&mut self) -> Option<Packet> {
+        None
     }
 }
 
-struct PacketOutput {
-    packets: HashMap<i64, PartialPacket>,
+// Wakes up address 0 with the last packet received, until it sends the same y twice in a row
+struct RepeatedWakeUp {
+    last: Option<Packet>,
+    prev_y_sent: Option<i64>,
 }
 
-impl PacketOutput {
-    pub fn new() -> PacketOutput {
-        PacketOutput {
-            packets: HashMap::new(),
-        }
+impl Nat for RepeatedWakeUp {
+    fn receive(&mut self, packet: Packet) -> Control {
+        self.last = Some(packet);
+        Control::Continue
     }
 
-    fn push_packet_piece(&mut self, origin: i64, value: i64) -> (i64, i64, i64) {
-        if !self.packets.contains_key(&origin) {
-            self.packets.insert(origin, PartialPacket::new(value));
-        } else {
-            let packet = self.packets.get_mut(&origin).unwrap();
-            packet.add_value(value);
-            if packet.is_complete() {
-                let ret = (packet.destination, packet.x, packet.y);
-                self.packets.remove(&origin);
-                return ret;
-            }
+    fn wake(&mut self) -> Option<Packet> {
+        let packet = self.last?;
+        if self.prev_y_sent == Some(packet.y) {
+            return None;
         }
-        (-1, 0, 0)
+        self.prev_y_sent = Some(packet.y);
+        Some(Packet {
+            destination: 0,
+            ..packet
+        })
     }
 }
@@ -1,8 +1,9 @@
 use crate::coords::{Coord, Direction};
+use crate::parse::{check_characters, read_text, ParseError};
 use crate::solver::Solver;
 use core::fmt;
 use std::collections::{HashMap, HashSet};
-use std::io::{BufReader, Read};
+use std::io::Read;
 
 pub struct Problem;
 
@@ -11,10 +12,10 @@ impl Solver for Problem {
     type Output1 = u32;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        let mut buffer = String::new();
-        BufReader::new(r).read_to_string(&mut buffer).expect("");
-        buffer
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, ParseError> {
+        let buffer = read_text(r)?;
+        check_characters(&buffer, "#.?")?;
+        Ok(buffer)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
@@ -1,6 +1,9 @@
+use crate::parse::ParseError;
 use crate::solver::Solver;
 use std::io::Read;
-use crate::intcode::{IntCode, IntInput, read_input};
+use crate::intcode::{IntCode, read_input};
+use crate::intcode::ascii::AsciiTerminal;
+use crate::diagln;
 use std::{io, thread};
 use std::time::Duration;
 
@@ -11,7 +14,7 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Vec<i64> {
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Vec<i64>, ParseError> {
         read_input(r)
     }
 
@@ -68,61 +71,41 @@ fn get_password(program: &Vec<i64>) {
     let item7 = 0;
     let item8 = 0;
 
-                                    println!("Trying: {},{},{},{},{},{},{},{}", item1,item2,item3,item4,item5,item6,item7,item8);
-                                    let mut intcode = IntCode::new(program);
+                                    let mut terminal = AsciiTerminal::new(IntCode::new(program));
 
                                     for c in commands.split('\n'){
-                                        send_command(&mut intcode, c.trim().to_string());
+                                        send_command(&mut terminal, c.trim().to_string());
                                     }
 
-                                    if item1 == 1 {send_command(&mut intcode, "drop hypercube".to_string());}
-                                    if item2 == 1 {send_command(&mut intcode, "drop tambourine".to_string());}
-                                    if item3 == 1 {send_command(&mut intcode, "drop astrolabe".to_string());}
-                                    if item4 == 1 {send_command(&mut intcode, "drop shell".to_string());}
-                                    if item5 == 1 {send_command(&mut intcode, "drop klein bottle".to_string());}
-                                    if item6 == 1 {send_command(&mut intcode, "drop easter egg".to_string());}
-                                    if item7 == 1 {send_command(&mut intcode, "drop dark matter".to_string());}
-                                    if item8 == 1 {send_command(&mut intcode, "drop coin".to_string());}
-                                    send_command(&mut intcode, "south".to_string());
+                                    if item1 == 1 {send_command(&mut terminal, "drop hypercube".to_string());}
+                                    if item2 == 1 {send_command(&mut terminal, "drop tambourine".to_string());}
+                                    if item3 == 1 {send_command(&mut terminal, "drop astrolabe".to_string());}
+                                    if item4 == 1 {send_command(&mut terminal, "drop shell".to_string());}
+                                    if item5 == 1 {send_command(&mut terminal, "drop klein bottle".to_string());}
+                                    if item6 == 1 {send_command(&mut terminal, "drop easter egg".to_string());}
+                                    if item7 == 1 {send_command(&mut terminal, "drop dark matter".to_string());}
+                                    if item8 == 1 {send_command(&mut terminal, "drop coin".to_string());}
+                                    send_command(&mut terminal, "south".to_string());
 
 }
 
-fn send_command(intcode: &mut IntCode, data: String ) {
-    for c in data.chars()  {
-        intcode.input.push(c as i64);
-    }
-    intcode.input.push(10);
-    let mut output = IntInput::new();
-    intcode.advance(&mut output);
-
-    // Print output
-    let mut buffer = String::new();
-    while output.has_input(){
-        let c = (output.get() as u8)  as char;
-        if c == '\n' {
-            //if buffer.contains("Alert!"){println!("{}",buffer)};
-            println!("{}", buffer);
-            buffer = "".to_string();}
-        buffer.push(c);
-    }
-
+fn send_command(terminal: &mut AsciiTerminal, data: String) {
+    terminal.send_line(&data);
+    let output = terminal.read_until_prompt("Command?\n").unwrap();
+    diagln!("{}", output.text);
 }
 
 fn interactive_run(program: &Vec<i64>) {
-    let mut intcode = IntCode::new(program);
-    let mut output = IntInput::new();
+    let mut terminal = AsciiTerminal::new(IntCode::new(program));
     let mut quit = false;
 
     while !quit {
         // Advance
-        intcode.advance(&mut output);
-
-        // Print output
-        while output.has_input(){
-            let c = (output.get() as u8)  as char;
-            print!("{}", c);
+        let output = terminal.read_until_blocked().unwrap();
+        println!("{}", output.text);
+        if terminal.halted() {
+            break;
         }
-        println!("");
 
         // Read input
         loop {
@@ -130,10 +113,25 @@ fn interactive_run(program: &Vec<i64>) {
             match io::stdin().read_line(&mut buffer) {
                 Ok(_) => {
                     buffer = buffer.trim().to_string();
-                    for c in buffer.chars() {
-                        intcode.input.push(c as i64);
+                    // Save and load are handled here and never reach the droid
+                    if let Some(path) = buffer.strip_prefix("save ") {
+                        match terminal.intcode.save_to_file(path) {
+                            Ok(_) => println!("Saved to {}", path),
+                            Err(e) => println!("Unable to save {}: {}", path, e),
+                        }
+                        continue;
+                    }
+                    if let Some(path) = buffer.strip_prefix("load ") {
+                        match IntCode::load_from_file(path) {
+                            Ok(loaded) => {
+                                terminal.intcode = loaded;
+                                println!("Loaded {}", path);
+                            }
+                            Err(e) => println!("Unable to load {}: {}", path, e),
+                        }
+                        continue;
                     }
-                    intcode.input.push(10);
+                    terminal.send_line(&buffer);
                     if buffer == "q".to_string() { quit = true };
                     break
                 },
@@ -141,4 +139,4 @@ fn interactive_run(program: &Vec<i64>) {
             }
         }
     }
-}
\ No newline at end of file
+}
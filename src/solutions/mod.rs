@@ -1,5 +1,5 @@
 // DO NOT EDIT THIS FILE
-use crate::solver::Solver;
+use crate::solver::{InputError, Part, Report, Solver};
 
 mod day01;
 mod day02;
@@ -27,33 +27,35 @@ mod day23;
 mod day24;
 mod day25;
 
-pub fn exec_day(day: i32) {
+pub const DAYS: &[i32] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25];
+
+pub fn exec_day(day: i32, input: &str, part: Part) -> Result<Report, InputError> {
     match day {
-        1 => day01::Problem {}.solve(day),
-        2 => day02::Problem {}.solve(day),
-        3 => day03::Problem {}.solve(day),
-        4 => day04::Problem {}.solve(day),
-        5 => day05::Problem {}.solve(day),
-        6 => day06::Problem {}.solve(day),
-        7 => day07::Problem {}.solve(day),
-        8 => day08::Problem {}.solve(day),
-        9 => day09::Problem {}.solve(day),
-        10 => day10::Problem {}.solve(day),
-        11 => day11::Problem {}.solve(day),
-        12 => day12::Problem {}.solve(day),
-        13 => day13::Problem {}.solve(day),
-        14 => day14::Problem {}.solve(day),
-        15 => day15::Problem {}.solve(day),
-        16 => day16::Problem {}.solve(day),
-        17 => day17::Problem {}.solve(day),
-        18 => day18::Problem {}.solve(day),
-        19 => day19::Problem {}.solve(day),
-        20 => day20::Problem {}.solve(day),
-        21 => day21::Problem {}.solve(day),
-        22 => day22::Problem {}.solve(day),
-        23 => day23::Problem {}.solve(day),
-        24 => day24::Problem {}.solve(day),
-        25 => day25::Problem {}.solve(day),
-        d => println!("Day {} hasn't been solved yet :(", d),
+        1 => day01::Problem {}.run(input, part),
+        2 => day02::Problem {}.run(input, part),
+        3 => day03::Problem {}.run(input, part),
+        4 => day04::Problem {}.run(input, part),
+        5 => day05::Problem {}.run(input, part),
+        6 => day06::Problem {}.run(input, part),
+        7 => day07::Problem {}.run(input, part),
+        8 => day08::Problem {}.run(input, part),
+        9 => day09::Problem {}.run(input, part),
+        10 => day10::Problem {}.run(input, part),
+        11 => day11::Problem {}.run(input, part),
+        12 => day12::Problem {}.run(input, part),
+        13 => day13::Problem {}.run(input, part),
+        14 => day14::Problem {}.run(input, part),
+        15 => day15::Problem {}.run(input, part),
+        16 => day16::Problem {}.run(input, part),
+        17 => day17::Problem {}.run(input, part),
+        18 => day18::Problem {}.run(input, part),
+        19 => day19::Problem {}.run(input, part),
+        20 => day20::Problem {}.run(input, part),
+        21 => day21::Problem {}.run(input, part),
+        22 => day22::Problem {}.run(input, part),
+        23 => day23::Problem {}.run(input, part),
+        24 => day24::Problem {}.run(input, part),
+        25 => day25::Problem {}.run(input, part),
+        d => panic!("Day {} hasn't been solved yet :(", d),
     }
 }
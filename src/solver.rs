@@ -1,31 +1,390 @@
-use std::{fmt::Display, fs::File, io, path::Path};
+use crate::intcode::budget::total_cycles;
+use crate::parse::ParseError;
+use core::fmt;
+use std::any::Any;
+use std::{
+    error::Error,
+    fmt::Display,
+    fs::File,
+    io::{self, Read},
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
 
-fn input_file(day: i32) -> String {
+// An input path of `-` reads the puzzle input from stdin
+pub const STDIN: &str = "-";
+
+pub fn input_file(day: i32) -> String {
     format!("input/day{:02}", day)
 }
 
+// Solvers print their own diagnostics, like the day 8 and 11 images, with `diag!` and
+// `diagln!`. They go to stdout unless JSON output needs it kept parseable
+static DIAGNOSTICS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_diagnostics_to_stderr(stderr: bool) {
+    DIAGNOSTICS_TO_STDERR.store(stderr, Ordering::Relaxed);
+}
+
+pub fn diagnostic(args: fmt::Arguments) {
+    if DIAGNOSTICS_TO_STDERR.load(Ordering::Relaxed) {
+        eprint!("{}", args);
+    } else {
+        print!("{}", args);
+    }
+}
+
+#[macro_export]
+macro_rules! diag {
+    ($($arg:tt)*) => {
+        $crate::solver::diagnostic(format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! diagln {
+    ($($arg:tt)*) => {{
+        $crate::solver::diagnostic(format_args!($($arg)*));
+        $crate::solver::diagnostic(format_args!("\n"));
+    }};
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    First,
+    Second,
+    Both,
+}
+
+impl Part {
+    pub fn first(self) -> bool {
+        self != Part::Second
+    }
+
+    pub fn second(self) -> bool {
+        self != Part::First
+    }
+}
+
+// A part's answer, or why its solver panicked, and how long it ran
+#[derive(Clone, Debug)]
+pub struct Answer {
+    pub value: Result<String, String>,
+    pub time: Duration,
+}
+
+// Parts that weren't asked for are left out. The parse time includes reading the input
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub parse_time: Duration,
+    pub first: Option<Answer>,
+    pub second: Option<Answer>,
+    // Intcode instructions executed while solving, 0 for days without Intcode
+    pub cycles: u64,
+}
+
+impl Report {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time
+            + [&self.first, &self.second]
+                .iter()
+                .flat_map(|a| a.as_ref().map(|a| a.time))
+                .sum::<Duration>()
+    }
+
+    // The first part that panicked, if any
+    pub fn error(&self) -> Option<String> {
+        [(1, &self.first), (2, &self.second)]
+            .iter()
+            .find_map(|(n, answer)| match answer.as_ref().map(|a| &a.value) {
+                Some(Err(message)) => Some(format!("part {} panicked: {}", n, message)),
+                _ => None,
+            })
+    }
+}
+
+// The message a solver panicked with
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => String::from("unknown panic"),
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Parsed in {}", format_duration(self.parse_time))?;
+        for (n, answer) in [(1, &self.first), (2, &self.second)] {
+            match answer {
+                Some(Answer {
+                    value: Ok(value),
+                    time,
+                }) => writeln!(f, "Solution {}: {} ({})", n, value, format_duration(*time))?,
+                Some(Answer {
+                    value: Err(message),
+                    time,
+                }) => writeln!(
+                    f,
+                    "Solution {} panicked after {}: {}",
+                    n,
+                    format_duration(*time),
+                    message
+                )?,
+                None => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+// Three significant digits or so, in the largest unit that keeps the value above 1
+pub fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos() as f64;
+    if nanos < 1e3 {
+        format!("{}ns", nanos)
+    } else if nanos < 1e6 {
+        format!("{:.1}µs", nanos / 1e3)
+    } else if nanos < 1e9 {
+        format!("{:.1}ms", nanos / 1e6)
+    } else {
+        format!("{:.2}s", nanos / 1e9)
+    }
+}
+
+// A single line JSON object describing a day's run for scripts. Times are in nanoseconds
+// and whatever wasn't computed is null
+pub fn json_record(day: i32, input: &str, result: &Result<Report, String>) -> String {
+    let answer = |answer: &Option<Answer>| match answer {
+        Some(a) => {
+            let (value, error) = match &a.value {
+                Ok(value) => (json_string(value), String::from("null")),
+                Err(message) => (String::from("null"), json_string(message)),
+            };
+            format!(
+                "{{\"answer\":{},\"time_ns\":{},\"error\":{}}}",
+                value,
+                a.time.as_nanos(),
+                error
+            )
+        }
+        None => String::from("null"),
+    };
+    let (parse, first, second, total, cycles, error) = match result {
+        Ok(r) => (
+            r.parse_time.as_nanos().to_string(),
+            answer(&r.first),
+            answer(&r.second),
+            r.total_time().as_nanos().to_string(),
+            r.cycles.to_string(),
+            r.error()
+                .map_or_else(|| String::from("null"), |e| json_string(&e)),
+        ),
+        Err(e) => {
+            let null = || String::from("null");
+            (null(), null(), null(), null(), null(), json_string(e))
+        }
+    };
+    format!(
+        "{{\"day\":{},\"input\":{},\"parse_time_ns\":{},\"part1\":{},\"part2\":{},\
+         \"total_time_ns\":{},\"cycles\":{},\"error\":{}}}",
+        day,
+        json_string(input),
+        parse,
+        first,
+        second,
+        total,
+        cycles,
+        error
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// Why an input file couldn't be turned into a puzzle input, along with its path
+#[derive(Debug)]
+pub enum InputError {
+    Io(String, io::Error),
+    Parse(String, ParseError),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(path, e) => write!(f, "unable to open {}: {}", path, e),
+            InputError::Parse(path, e) => {
+                write!(f, "{}:{}:{}: {}", path, e.line, e.column, e.message)
+            }
+        }
+    }
+}
+
+impl Error for InputError {}
+
 pub trait Solver {
     type Input;
     type Output1: Display;
     type Output2: Display;
 
-    fn parse_input<R: io::Seek + io::Read>(&self, r: R) -> Self::Input;
+    fn parse_input<R: io::Seek + io::Read>(&self, r: R) -> Result<Self::Input, ParseError>;
     fn solve_first(&self, input: &Self::Input) -> Self::Output1;
     fn solve_second(&self, input: &Self::Input) -> Self::Output2;
 
-    fn load_input<P: AsRef<Path>>(&self, p: P) -> io::Result<Self::Input> {
-        let f = File::open(p)?;
-        Ok(self.parse_input(f))
+    fn load_input(&self, path: &str) -> Result<Self::Input, InputError> {
+        // Stdin can't seek, so it's read up front
+        if path == STDIN {
+            let mut bytes = Vec::new();
+            io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|e| InputError::Io(String::from("stdin"), e))?;
+            return self
+                .parse_input(io::Cursor::new(bytes))
+                .map_err(|e| InputError::Parse(String::from("stdin"), e));
+        }
+        let f = File::open(path).map_err(|e| InputError::Io(path.to_string(), e))?;
+        self.parse_input(f)
+            .map_err(|e| InputError::Parse(path.to_string(), e))
     }
 
-    fn solve(&self, day: i32) {
-        let input_file = input_file(day);
-        let input = self
-            .load_input(input_file)
-            .expect("unable to open input file");
-        let s1 = self.solve_first(&input);
-        let s2 = self.solve_second(&input);
-        println!("Solution 1: {}", s1);
-        println!("Solution 2: {}", s2);
+    fn run(&self, path: &str, part: Part) -> Result<Report, InputError> {
+        let cycles = total_cycles();
+        let start = Instant::now();
+        let input = self.load_input(path)?;
+        let parse_time = start.elapsed();
+        // A part that panics doesn't take the other part or the timings with it
+        let timed = |solve: &dyn Fn() -> String| {
+            let start = Instant::now();
+            let value = panic::catch_unwind(AssertUnwindSafe(solve))
+                .map_err(|payload| panic_message(payload.as_ref()));
+            Answer {
+                value,
+                time: start.elapsed(),
+            }
+        };
+        let first = if part.first() {
+            Some(timed(&|| self.solve_first(&input).to_string()))
+        } else {
+            None
+        };
+        let second = if part.second() {
+            Some(timed(&|| self.solve_second(&input).to_string()))
+        } else {
+            None
+        };
+        Ok(Report {
+            parse_time,
+            first,
+            second,
+            cycles: total_cycles() - cycles,
+        })
     }
+
+    fn solve(&self, path: &str, part: Part) -> Result<(), InputError> {
+        print!("{}", self.run(path, part)?);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_report() {
+    assert_eq!(format_duration(Duration::from_nanos(850)), "850ns");
+    assert_eq!(format_duration(Duration::from_nanos(12_345)), "12.3µs");
+    assert_eq!(format_duration(Duration::from_micros(4_560)), "4.6ms");
+    assert_eq!(format_duration(Duration::from_millis(2_500)), "2.50s");
+
+    let report = Report {
+        parse_time: Duration::from_micros(10),
+        first: None,
+        second: Some(Answer {
+            value: Ok(String::from("42")),
+            time: Duration::from_micros(5),
+        }),
+        cycles: 0,
+    };
+    assert_eq!(report.total_time(), Duration::from_micros(15));
+    assert_eq!(
+        report.to_string(),
+        "Parsed in 10.0µs\nSolution 2: 42 (5.0µs)\n"
+    );
+}
+
+#[test]
+fn test_json_record() {
+    let report = Report {
+        parse_time: Duration::from_nanos(1500),
+        first: Some(Answer {
+            value: Ok(String::from("FPU\"AR")),
+            time: Duration::from_nanos(250),
+        }),
+        second: None,
+        cycles: 7366,
+    };
+    assert_eq!(
+        json_record(8, "input/day08", &Ok(report)),
+        "{\"day\":8,\"input\":\"input/day08\",\"parse_time_ns\":1500,\
+         \"part1\":{\"answer\":\"FPU\\\"AR\",\"time_ns\":250,\"error\":null},\
+         \"part2\":null,\"total_time_ns\":1750,\"cycles\":7366,\"error\":null}"
+    );
+    assert_eq!(
+        json_record(3, "-", &Err(String::from("stdin:1:1: bad\tinput"))),
+        "{\"day\":3,\"input\":\"-\",\"parse_time_ns\":null,\"part1\":null,\
+         \"part2\":null,\"total_time_ns\":null,\"cycles\":null,\
+         \"error\":\"stdin:1:1: bad\\tinput\"}"
+    );
+}
+
+#[test]
+fn test_run_part_panics() {
+    struct Problem;
+
+    impl Solver for Problem {
+        type Input = usize;
+        type Output1 = usize;
+        type Output2 = usize;
+
+        fn parse_input<R: io::Seek + io::Read>(&self, mut r: R) -> Result<usize, ParseError> {
+            let mut bytes = Vec::new();
+            r.read_to_end(&mut bytes).unwrap();
+            Ok(bytes.len())
+        }
+
+        fn solve_first(&self, input: &usize) -> usize {
+            *input
+        }
+
+        fn solve_second(&self, _: &usize) -> usize {
+            panic!("no second part")
+        }
+    }
+
+    let report = Problem.run("Cargo.toml", Part::Both).unwrap();
+    assert!(report.first.clone().unwrap().value.is_ok());
+    let second = report.second.clone().unwrap();
+    assert_eq!(second.value, Err(String::from("no second part")));
+    assert_eq!(
+        report.error(),
+        Some(String::from("part 2 panicked: no second part"))
+    );
+
+    let record = json_record(1, "Cargo.toml", &Ok(report));
+    assert!(record.contains("\"part2\":{\"answer\":null,"));
+    assert!(record.ends_with("\"error\":\"part 2 panicked: no second part\"}"));
 }